@@ -1,4 +1,6 @@
-use crate::types::{DataKey, Error};
+use crate::mutual_aid::REPORTED_BY_DONORS;
+use crate::requests;
+use crate::types::{DataKey, Error, HelpRequestStatus, LimitKey, MatchingKey, PostingBond};
 use crate::{access::check_admin, types::HelpRequest};
 use soroban_sdk::{contract, contractevent, contractimpl, panic_with_error, token, Address, Env};
 
@@ -17,12 +19,63 @@ pub struct TokenAdded {
     token: Address,
 }
 
+#[contractevent]
+pub struct PriceOracleSet {
+    oracle: Address,
+    max_price_age: u64,
+}
+
+#[contractevent]
+pub struct DonationGracePeriodSet {
+    seconds: u64,
+}
+
+#[contractevent]
+pub struct ReportThresholdSet {
+    threshold_bps: u32,
+}
+
+#[contractevent]
+pub struct PostingBondSet {
+    token: Address,
+    amount: i128,
+}
+
+#[contractevent]
+pub struct MinPoolDepositSet {
+    token: Address,
+    amount: i128,
+}
+
+#[contractevent]
+pub struct ProfileVerificationChanged {
+    user: Address,
+    is_verified: bool,
+}
+
+#[contractevent]
+pub struct CreatorLimitsSet {
+    max_active_requests: u32,
+    max_active_giveaways: u32,
+}
+
+#[contractevent]
+pub struct MaxOpenDurationSet {
+    seconds: u64,
+}
+
 #[contractevent]
 pub struct RequestVerificationChanged {
     request_id: u64,
     is_verified: bool,
 }
 
+#[contractevent]
+pub struct DisputeResolved {
+    request_id: u64,
+    upheld: bool,
+}
+
 #[contractimpl]
 impl AdminContract {
     /// Emergency withdraw function - callable only by Admin
@@ -76,6 +129,212 @@ impl AdminContract {
         TokenAdded { token }.publish(&env);
     }
 
+    /// Configure the price oracle used for reference-currency goals - callable only by Admin
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `oracle` - The price oracle contract address
+    /// * `max_price_age` - Oldest price, in seconds, accepted when valuing a donation
+    ///
+    /// # Panics
+    /// Panics if called by non-admin address
+    pub fn set_price_oracle(env: Env, oracle: Address, max_price_age: u64) {
+        check_admin(&env);
+
+        env.storage().instance().set(&DataKey::PriceOracle, &oracle);
+        env.storage()
+            .instance()
+            .set(&DataKey::MaxPriceAge, &max_price_age);
+
+        PriceOracleSet {
+            oracle,
+            max_price_age,
+        }
+        .publish(&env);
+    }
+
+    /// Set how long donors can retract a donation - callable only by Admin
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `seconds` - Grace period after each donation
+    ///
+    /// # Panics
+    /// Panics if called by non-admin address
+    pub fn set_donation_grace_period(env: Env, seconds: u64) {
+        check_admin(&env);
+
+        env.storage()
+            .instance()
+            .set(&DataKey::DonationGracePeriod, &seconds);
+
+        DonationGracePeriodSet { seconds }.publish(&env);
+    }
+
+    /// Set how long a help request may stay Open before anyone can expire
+    /// it - callable only by Admin
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `seconds` - Maximum time since posting; 0 disables expiry
+    ///
+    /// # Panics
+    /// Panics if called by non-admin address
+    pub fn set_max_open_duration(env: Env, seconds: u64) {
+        check_admin(&env);
+
+        env.storage()
+            .instance()
+            .set(&DataKey::MaxOpenDuration, &seconds);
+
+        MaxOpenDurationSet { seconds }.publish(&env);
+    }
+
+    /// Set the share of a request's raised amount that donor reports must
+    /// reach before the request is frozen - callable only by Admin
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `threshold_bps` - Threshold in basis points, between 1 and 10000
+    ///
+    /// # Panics
+    /// Panics if called by non-admin address or the threshold is out of range
+    pub fn set_report_threshold(env: Env, threshold_bps: u32) {
+        check_admin(&env);
+
+        if threshold_bps == 0 || threshold_bps > 10_000 {
+            panic_with_error!(&env, Error::InvalidThreshold);
+        }
+
+        env.storage()
+            .instance()
+            .set(&DataKey::ReportThreshold, &threshold_bps);
+
+        ReportThresholdSet { threshold_bps }.publish(&env);
+    }
+
+    /// Require a bond from creators posting a help request - callable only by Admin
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `token` - Whitelisted token the bond is paid in
+    /// * `amount` - Bond per request; 0 disables the bond
+    ///
+    /// # Panics
+    /// Panics if called by non-admin address, the token is not whitelisted
+    /// or the amount is negative
+    pub fn set_posting_bond(env: Env, token: Address, amount: i128) {
+        check_admin(&env);
+
+        if amount < 0 {
+            panic_with_error!(&env, Error::InvalidBondAmount);
+        }
+
+        if !env
+            .storage()
+            .instance()
+            .has(&DataKey::AllowedToken(token.clone()))
+        {
+            panic_with_error!(&env, Error::TokenNotSupported);
+        }
+
+        if amount == 0 {
+            env.storage().instance().remove(&DataKey::PostingBond);
+        } else {
+            let bond = PostingBond {
+                token: token.clone(),
+                amount,
+            };
+            env.storage().instance().set(&DataKey::PostingBond, &bond);
+        }
+
+        PostingBondSet { token, amount }.publish(&env);
+    }
+
+    /// Set the smallest deposit a sponsor must make to open a matching pool
+    /// in `token` - callable only by Admin
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `token` - Whitelisted token the pools are funded in
+    /// * `amount` - Minimum deposit in the token's base units
+    ///
+    /// # Panics
+    /// Panics if called by non-admin address, the token is not whitelisted
+    /// or the amount is not positive
+    pub fn set_min_pool_deposit(env: Env, token: Address, amount: i128) {
+        check_admin(&env);
+
+        if amount <= 0 {
+            panic_with_error!(&env, Error::InvalidMatchingPool);
+        }
+
+        if !env
+            .storage()
+            .instance()
+            .has(&DataKey::AllowedToken(token.clone()))
+        {
+            panic_with_error!(&env, Error::TokenNotSupported);
+        }
+
+        env.storage()
+            .instance()
+            .set(&MatchingKey::MinPoolDeposit(token.clone()), &amount);
+
+        MinPoolDepositSet { token, amount }.publish(&env);
+    }
+
+    /// Cap how many unfinished help requests and active giveaways a single
+    /// creator may have at once - callable only by Admin
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `max_active_requests` - Open, FullyFunded or Frozen requests per creator; 0 means no limit
+    /// * `max_active_giveaways` - Active giveaways per creator; 0 means no limit
+    ///
+    /// # Panics
+    /// Panics if called by non-admin address
+    pub fn set_creator_limits(env: Env, max_active_requests: u32, max_active_giveaways: u32) {
+        check_admin(&env);
+
+        env.storage()
+            .instance()
+            .set(&LimitKey::MaxActiveRequests, &max_active_requests);
+        env.storage()
+            .instance()
+            .set(&LimitKey::MaxActiveGiveaways, &max_active_giveaways);
+
+        CreatorLimitsSet {
+            max_active_requests,
+            max_active_giveaways,
+        }
+        .publish(&env);
+    }
+
+    /// Mark a user's profile as verified, or revoke it - callable only by Admin
+    /// Verified creators post help requests without a bond
+    ///
+    /// # Arguments
+    /// * `env` - The contract environment
+    /// * `user` - The profile owner
+    ///
+    /// # Panics
+    /// Panics if called by non-admin address
+    pub fn toggle_profile_verification(env: Env, user: Address) {
+        check_admin(&env);
+
+        let verified_key = DataKey::VerifiedProfile(user.clone());
+        let is_verified = !env.storage().persistent().has(&verified_key);
+
+        if is_verified {
+            env.storage().persistent().set(&verified_key, &true);
+        } else {
+            env.storage().persistent().remove(&verified_key);
+        }
+
+        ProfileVerificationChanged { user, is_verified }.publish(&env);
+    }
+
     pub fn toggle_request_verification(env: Env, request_id: u64) {
         check_admin(&env);
 
@@ -95,4 +354,130 @@ impl AdminContract {
         }
         .publish(&env);
     }
+
+    /// Freeze a help request pending investigation - callable only by Admin
+    ///
+    /// Blocks donations and withdrawals until the request is unfrozen or
+    /// force-cancelled.
+    ///
+    /// # Arguments
+    /// * `request_id` - The request to freeze
+    /// * `reason` - Moderation reason code stored on the request
+    ///
+    /// # Panics
+    /// Panics if called by non-admin address or the request is not Open or
+    /// FullyFunded
+    pub fn freeze_request(env: Env, request_id: u64, reason: u32) {
+        check_admin(&env);
+
+        let request_key = DataKey::HelpRequest(request_id);
+        let mut request: HelpRequest = env
+            .storage()
+            .persistent()
+            .get(&request_key)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::HelpRequestNotFound));
+
+        if request.status != HelpRequestStatus::Open
+            && request.status != HelpRequestStatus::FullyFunded
+        {
+            panic_with_error!(&env, Error::InvalidStatus);
+        }
+
+        requests::freeze(&env, &mut request, reason);
+    }
+
+    /// Lift a freeze - callable only by Admin
+    ///
+    /// The request returns to FullyFunded if its goal has been reached,
+    /// otherwise to Open.
+    ///
+    /// # Panics
+    /// Panics if called by non-admin address or the request is not Frozen
+    pub fn unfreeze_request(env: Env, request_id: u64) {
+        check_admin(&env);
+
+        let request_key = DataKey::HelpRequest(request_id);
+        let mut request: HelpRequest = env
+            .storage()
+            .persistent()
+            .get(&request_key)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::HelpRequestNotFound));
+
+        if request.status != HelpRequestStatus::Frozen {
+            panic_with_error!(&env, Error::InvalidStatus);
+        }
+
+        requests::unfreeze(&env, &mut request);
+    }
+
+    /// Cancel a fraudulent request - callable only by Admin
+    ///
+    /// Donors can then recover what is left of their donations through
+    /// `claim_refund` or `process_refunds`.
+    ///
+    /// # Arguments
+    /// * `request_id` - The request to cancel
+    /// * `reason` - Moderation reason code stored on the request
+    ///
+    /// # Panics
+    /// Panics if called by non-admin address or the request is already
+    /// Closed or Cancelled
+    pub fn force_cancel_request(env: Env, request_id: u64, reason: u32) {
+        check_admin(&env);
+
+        let request_key = DataKey::HelpRequest(request_id);
+        let mut request: HelpRequest = env
+            .storage()
+            .persistent()
+            .get(&request_key)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::HelpRequestNotFound));
+
+        if request.status == HelpRequestStatus::Closed
+            || request.status == HelpRequestStatus::Cancelled
+        {
+            panic_with_error!(&env, Error::InvalidStatus);
+        }
+
+        requests::force_cancel(&env, &mut request, reason);
+    }
+
+    /// Resolve the dispute on a frozen request - callable only by Admin
+    ///
+    /// An upheld dispute force-cancels the request so donors can be
+    /// refunded through `claim_refund` or `process_refunds`. A dismissed
+    /// dispute lifts the freeze and clears the reported weight; donors who
+    /// already reported cannot report again.
+    ///
+    /// # Arguments
+    /// * `request_id` - The frozen request
+    /// * `upheld` - Whether the reports were found to be valid
+    ///
+    /// # Panics
+    /// Panics if called by non-admin address or the request is not Frozen
+    pub fn resolve_dispute(env: Env, request_id: u64, upheld: bool) {
+        check_admin(&env);
+
+        let request_key = DataKey::HelpRequest(request_id);
+        let mut request: HelpRequest = env
+            .storage()
+            .persistent()
+            .get(&request_key)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::HelpRequestNotFound));
+
+        if request.status != HelpRequestStatus::Frozen {
+            panic_with_error!(&env, Error::InvalidStatus);
+        }
+
+        if upheld {
+            let reason = request.moderation_reason.unwrap_or(REPORTED_BY_DONORS);
+            requests::force_cancel(&env, &mut request, reason);
+        } else {
+            env.storage()
+                .persistent()
+                .remove(&DataKey::ReportWeight(request_id));
+            requests::unfreeze(&env, &mut request);
+        }
+
+        DisputeResolved { request_id, upheld }.publish(&env);
+    }
 }
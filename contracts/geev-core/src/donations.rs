@@ -0,0 +1,662 @@
+use crate::leaderboard;
+use crate::oracle::{Asset, PriceOracleClient};
+use crate::requests;
+use crate::types::{
+    Contribution, DataKey, DonationNote, Error, FundingRound, HelpRequest, HelpRequestStatus,
+    LeaderboardKey, MatchEligibility, MatchingKey, MatchingPool, RecentDonation, RoundKey,
+    RoundStatus,
+};
+use crate::utils::isqrt;
+use soroban_sdk::{
+    contractevent, panic_with_error, token, xdr::ToXdr, Address, Bytes, BytesN, Env, Map, String,
+    Vec,
+};
+
+/// Default time, in seconds, during which a donor can retract a donation.
+const DEFAULT_DONATION_GRACE_PERIOD: u64 = 24 * 60 * 60;
+
+/// Contributions are scaled by this factor before taking square roots so
+/// that quadratic-funding weights keep three decimal digits of precision.
+const QF_PRECISION: i128 = 1_000_000;
+
+/// Maximum length, in bytes, of a donation's "dedicated to" field.
+const MAX_DEDICATION_LEN: u32 = 64;
+
+#[contractevent]
+pub struct DonationReceived {
+    request_id: u64,
+    donor: Address,
+    amount: i128,
+    message_hash: Option<BytesN<32>>,
+    dedicated_to: Option<String>,
+}
+
+/// Published instead of `DonationReceived` for anonymous donations.
+/// `commitment` is `sha256(donor XDR || salt)`, so the donor can later
+/// prove the donation without the event revealing who made it.
+#[contractevent]
+pub struct AnonymousDonationReceived {
+    request_id: u64,
+    commitment: BytesN<32>,
+    amount: i128,
+    message_hash: Option<BytesN<32>>,
+    dedicated_to: Option<String>,
+}
+
+#[contractevent]
+pub struct MatchApplied {
+    pool_id: u64,
+    request_id: u64,
+    /// `None` when matching an anonymous donation.
+    donor: Option<Address>,
+    amount: i128,
+}
+
+/// Load a help request that can currently take donations.
+pub fn load_donatable(env: &Env, request_id: u64) -> HelpRequest {
+    let request: HelpRequest = env
+        .storage()
+        .persistent()
+        .get(&DataKey::HelpRequest(request_id))
+        .unwrap_or_else(|| panic_with_error!(env, Error::HelpRequestNotFound));
+
+    if request.status == HelpRequestStatus::FullyFunded {
+        panic_with_error!(env, Error::HelpRequestAlreadyFullyFunded);
+    }
+
+    if request.status != HelpRequestStatus::Open {
+        panic_with_error!(env, Error::InvalidStatus);
+    }
+
+    request
+}
+
+/// Account for `amount` the contract has already received from `donor`:
+/// donation and raised totals, round contributions, pool matches and the
+/// retraction grace period. Each donation is also appended to the
+/// request's contribution log with its `note`. With an `anonymous_salt`
+/// the donor is kept out of events, leaderboards and the log.
+pub fn record(
+    env: &Env,
+    mut request: HelpRequest,
+    donor: &Address,
+    amount: i128,
+    anonymous_salt: Option<BytesN<32>>,
+    note: Option<DonationNote>,
+) {
+    let request_id = request.id;
+    let (message_hash, dedicated_to) = match note {
+        Some(note) => (note.message_hash, note.dedicated_to),
+        None => (None, None),
+    };
+    if dedicated_to
+        .as_ref()
+        .is_some_and(|name| name.is_empty() || name.len() > MAX_DEDICATION_LEN)
+    {
+        panic_with_error!(env, Error::InvalidDonationNote);
+    }
+    let request_key = DataKey::HelpRequest(request_id);
+
+    // ✅ Track individual donation for refund logic
+    let donation_key = DataKey::Donation(request_id, donor.clone());
+    let previous_donation: i128 = env.storage().persistent().get(&donation_key).unwrap_or(0);
+    let new_donation = previous_donation
+        .checked_add(amount)
+        .unwrap_or_else(|| panic_with_error!(env, Error::ArithmeticOverflow));
+    env.storage().persistent().set(&donation_key, &new_donation);
+    index_donor(env, request_id, donor);
+    let commitment = anonymous_salt.map(|salt| {
+        let mut preimage: Bytes = donor.clone().to_xdr(env);
+        preimage.append(&Bytes::from_array(env, &salt.to_array()));
+        env.crypto().sha256(&preimage).to_bytes()
+    });
+    if let Some(commitment) = &commitment {
+        hide_donor(env, &request, donor, commitment);
+    } else {
+        record_giving(env, &request, donor, amount);
+    }
+
+    let round_id = record_round_contribution(env, &request, donor, amount);
+
+    // ✅ Explicit overflow check for total raised
+    let new_raised = request
+        .raised_amount
+        .checked_add(amount)
+        .unwrap_or_else(|| panic_with_error!(env, Error::ArithmeticOverflow));
+
+    request.raised_amount = new_raised;
+    let value = record_value(env, request_id, &request.token, amount);
+
+    let public_donor = commitment.is_none().then_some(donor);
+    let (matches, matched_value) = apply_matches(env, &mut request, public_donor, amount);
+
+    if requests::goal_reached(env, &request) {
+        requests::set_status(env, &mut request, HelpRequestStatus::FullyFunded);
+    }
+
+    env.storage().persistent().set(&request_key, &request);
+
+    // Keep every donation that can still be retracted
+    let now = env.ledger().timestamp();
+    let grace_period = donation_grace_period(env);
+    let recent_key = DataKey::RecentDonation(request_id, donor.clone());
+    let mut recent_donations: Vec<RecentDonation> = Vec::new(env);
+    for recent in env
+        .storage()
+        .persistent()
+        .get::<_, Vec<RecentDonation>>(&recent_key)
+        .unwrap_or(Vec::new(env))
+        .iter()
+    {
+        if now <= recent.donated_at.saturating_add(grace_period) {
+            recent_donations.push_back(recent);
+        }
+    }
+    recent_donations.push_back(RecentDonation {
+        amount,
+        donated_at: now,
+        value: value + matched_value,
+        round_id,
+        matches,
+    });
+    env.storage()
+        .persistent()
+        .set(&recent_key, &recent_donations);
+
+    log_contribution(
+        env,
+        request_id,
+        public_donor.cloned(),
+        amount,
+        message_hash.clone(),
+        dedicated_to.clone(),
+    );
+
+    match commitment {
+        Some(commitment) => {
+            AnonymousDonationReceived {
+                request_id,
+                commitment,
+                amount,
+                message_hash,
+                dedicated_to,
+            }
+            .publish(env);
+        }
+        None => DonationReceived {
+            request_id,
+            donor: donor.clone(),
+            amount,
+            message_hash,
+            dedicated_to,
+        }
+        .publish(env),
+    }
+}
+
+/// Account for a settled pledge of `amount` the contract has already
+/// received from `donor`. Unlike `record`, the donation is not matched,
+/// counted in funding rounds or open to retraction, and the caller stores
+/// `request`.
+pub fn record_pledge(env: &Env, request: &mut HelpRequest, donor: &Address, amount: i128) {
+    let donation_key = DataKey::Donation(request.id, donor.clone());
+    let donation: i128 = env
+        .storage()
+        .persistent()
+        .get::<_, i128>(&donation_key)
+        .unwrap_or(0)
+        .checked_add(amount)
+        .unwrap_or_else(|| panic_with_error!(env, Error::ArithmeticOverflow));
+    env.storage().persistent().set(&donation_key, &donation);
+    index_donor(env, request.id, donor);
+    record_giving(env, request, donor, amount);
+
+    request.raised_amount = request
+        .raised_amount
+        .checked_add(amount)
+        .unwrap_or_else(|| panic_with_error!(env, Error::ArithmeticOverflow));
+    record_value(env, request.id, &request.token, amount);
+
+    log_contribution(env, request.id, Some(donor.clone()), amount, None, None);
+
+    DonationReceived {
+        request_id: request.id,
+        donor: donor.clone(),
+        amount,
+        message_hash: None,
+        dedicated_to: None,
+    }
+    .publish(env);
+}
+
+/// Append a donation to the request's contribution log.
+pub fn log_contribution(
+    env: &Env,
+    request_id: u64,
+    donor: Option<Address>,
+    amount: i128,
+    message_hash: Option<BytesN<32>>,
+    dedicated_to: Option<String>,
+) {
+    let count_key = DataKey::ContributionCount(request_id);
+    let index: u32 = env.storage().persistent().get(&count_key).unwrap_or(0);
+    let contribution = Contribution {
+        index,
+        donor,
+        amount,
+        message_hash,
+        dedicated_to,
+        donated_at: env.ledger().timestamp(),
+    };
+    env.storage()
+        .persistent()
+        .set(&DataKey::Contribution(request_id, index), &contribution);
+
+    let next_index = index
+        .checked_add(1)
+        .unwrap_or_else(|| panic_with_error!(env, Error::ArithmeticOverflow));
+    env.storage().persistent().set(&count_key, &next_index);
+}
+
+/// Append `donor` to the request's donor index the first time they give,
+/// in any token or through a pool match or funding round allocation.
+pub fn index_donor(env: &Env, request_id: u64, donor: &Address) {
+    let has_donated_key = DataKey::HasDonated(request_id, donor.clone());
+    if env.storage().persistent().has(&has_donated_key) {
+        return;
+    }
+    env.storage().persistent().set(&has_donated_key, &true);
+
+    let count_key = DataKey::DonorCount(request_id);
+    let count: u32 = env.storage().persistent().get(&count_key).unwrap_or(0);
+    env.storage()
+        .persistent()
+        .set(&DataKey::DonorIndex(request_id, count), donor);
+    env.storage().persistent().set(&count_key, &(count + 1));
+}
+
+/// Apply a change of `delta` in what `donor` has given to `request` to
+/// the request's top donors and the global ranking for its token. A
+/// negative `delta` never takes off more than earlier calls added.
+/// Donors who have given anonymously to the request are skipped.
+pub fn record_giving(env: &Env, request: &HelpRequest, donor: &Address, delta: i128) {
+    if env
+        .storage()
+        .persistent()
+        .has(&DataKey::AnonymousDonor(request.id, donor.clone()))
+    {
+        return;
+    }
+
+    // Never take off more than was added here
+    let given_key = LeaderboardKey::Given(request.id, donor.clone());
+    let given: i128 = env.storage().persistent().get(&given_key).unwrap_or(0);
+    let delta = delta.max(-given);
+    let given = given
+        .checked_add(delta)
+        .unwrap_or_else(|| panic_with_error!(env, Error::ArithmeticOverflow));
+    env.storage().persistent().set(&given_key, &given);
+
+    leaderboard::update(env, &LeaderboardKey::TopDonors(request.id), donor, given);
+    record_token_giving(env, &request.token, donor, delta);
+}
+
+/// Take `donor` off the request's leaderboards the first time they give
+/// to it anonymously, including what they had given publicly before.
+/// That first `commitment` is kept for the donor's later refund and
+/// retraction events.
+pub fn hide_donor(env: &Env, request: &HelpRequest, donor: &Address, commitment: &BytesN<32>) {
+    let anonymous_key = DataKey::AnonymousDonor(request.id, donor.clone());
+    if env.storage().persistent().has(&anonymous_key) {
+        return;
+    }
+    env.storage().persistent().set(&anonymous_key, commitment);
+
+    let given_key = LeaderboardKey::Given(request.id, donor.clone());
+    let given: i128 = env.storage().persistent().get(&given_key).unwrap_or(0);
+    env.storage().persistent().remove(&given_key);
+
+    leaderboard::update(env, &LeaderboardKey::TopDonors(request.id), donor, 0);
+    if given > 0 {
+        record_token_giving(env, &request.token, donor, -given);
+    }
+}
+
+/// The commitment `donor` first gave anonymously to the request under,
+/// if they have.
+pub fn donor_commitment(env: &Env, request_id: u64, donor: &Address) -> Option<BytesN<32>> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::AnonymousDonor(request_id, donor.clone()))
+}
+
+pub fn record_token_giving(env: &Env, token: &Address, donor: &Address, delta: i128) {
+    let total_key = LeaderboardKey::TotalGiven(donor.clone(), token.clone());
+    let total: i128 = env
+        .storage()
+        .persistent()
+        .get::<_, i128>(&total_key)
+        .unwrap_or(0)
+        .checked_add(delta)
+        .unwrap_or_else(|| panic_with_error!(env, Error::ArithmeticOverflow));
+    env.storage().persistent().set(&total_key, &total);
+    leaderboard::update(
+        env,
+        &LeaderboardKey::TopDonorsByToken(token.clone()),
+        donor,
+        total,
+    );
+}
+
+/// Track `donor`'s contribution towards the quadratic-funding round the
+/// request is taking part in, if any.
+///
+/// Each donor's running total is kept per round and request, and the
+/// request's sum of square roots is adjusted by the change in that donor's
+/// square root, so repeat donations by one address are not counted as
+/// several unique donors.
+///
+/// Returns the round the contribution was counted in, if any.
+pub fn record_round_contribution(
+    env: &Env,
+    request: &HelpRequest,
+    donor: &Address,
+    amount: i128,
+) -> Option<u64> {
+    let round_id: u64 = env
+        .storage()
+        .persistent()
+        .get(&RoundKey::RequestRound(request.id))?;
+    let round: FundingRound = env
+        .storage()
+        .persistent()
+        .get(&RoundKey::FundingRound(round_id))?;
+
+    let now = env.ledger().timestamp();
+    if round.status != RoundStatus::Active || now < round.start_time || now > round.end_time {
+        return None;
+    }
+
+    if round.require_profile
+        && !env
+            .storage()
+            .persistent()
+            .has(&DataKey::Profile(donor.clone()))
+    {
+        return None;
+    }
+
+    adjust_round_contribution(env, round_id, request.id, donor, amount);
+
+    Some(round_id)
+}
+
+/// Add `delta` (which may be negative) to a donor's contribution in a
+/// round and update the request's sum of square roots to match.
+pub fn adjust_round_contribution(
+    env: &Env,
+    round_id: u64,
+    request_id: u64,
+    donor: &Address,
+    delta: i128,
+) {
+    let contribution_key = RoundKey::RoundContribution(round_id, request_id, donor.clone());
+    let previous: i128 = env
+        .storage()
+        .persistent()
+        .get(&contribution_key)
+        .unwrap_or(0);
+    let updated = previous
+        .checked_add(delta)
+        .unwrap_or_else(|| panic_with_error!(env, Error::ArithmeticOverflow))
+        .max(0);
+    env.storage().persistent().set(&contribution_key, &updated);
+
+    let scaled_sqrt = |value: i128| {
+        value
+            .checked_mul(QF_PRECISION)
+            .map(isqrt)
+            .unwrap_or_else(|| panic_with_error!(env, Error::ArithmeticOverflow))
+    };
+
+    let sqrt_sum_key = RoundKey::RoundSqrtSum(round_id, request_id);
+    let sqrt_sum: i128 = env.storage().persistent().get(&sqrt_sum_key).unwrap_or(0);
+    let new_sqrt_sum = sqrt_sum - scaled_sqrt(previous) + scaled_sqrt(updated);
+    env.storage().persistent().set(&sqrt_sum_key, &new_sqrt_sum);
+}
+
+/// Top up `request` from every active pool that is eligible to match a
+/// donation of `amount`.
+///
+/// Only the active pools indexed under the request's token or the request
+/// itself are read. A match never takes the request past its goal.
+///
+/// Matched funds are credited to the sponsor's `DataKey::Donation` entry,
+/// so if the request is later cancelled the sponsor reclaims its share
+/// through `claim_refund` like any other donor.
+///
+/// Returns the amount matched by each pool and the total reference-currency
+/// value recorded for those matches.
+pub fn apply_matches(
+    env: &Env,
+    request: &mut HelpRequest,
+    donor: Option<&Address>,
+    amount: i128,
+) -> (Map<u64, i128>, i128) {
+    let mut matches = Map::new(env);
+    let mut matched_value: i128 = 0;
+
+    let mut candidates: Vec<u64> = env
+        .storage()
+        .persistent()
+        .get(&MatchingKey::TokenPools(request.token.clone()))
+        .unwrap_or(Vec::new(env));
+    let request_pools: Vec<u64> = env
+        .storage()
+        .persistent()
+        .get(&MatchingKey::RequestPools(request.id))
+        .unwrap_or(Vec::new(env));
+    candidates.append(&request_pools);
+
+    for pool_id in candidates.iter() {
+        let pool_key = MatchingKey::MatchingPool(pool_id);
+        let mut pool: MatchingPool = match env.storage().persistent().get(&pool_key) {
+            Some(pool) => pool,
+            None => continue,
+        };
+
+        let eligible = pool.token == request.token
+            && match pool.eligibility {
+                MatchEligibility::AllRequests => true,
+                MatchEligibility::VerifiedOnly => request.is_verified,
+                MatchEligibility::Request(id) => id == request.id,
+            };
+        if !eligible {
+            continue;
+        }
+
+        let still_needed = still_needed(env, request);
+        if still_needed <= 0 {
+            break;
+        }
+
+        let matched_key = MatchingKey::PoolMatched(pool_id, request.id);
+        let already_matched: i128 = env.storage().persistent().get(&matched_key).unwrap_or(0);
+
+        let wanted = amount
+            .checked_mul(pool.ratio_bps as i128)
+            .and_then(|v| v.checked_div(10_000))
+            .unwrap_or_else(|| panic_with_error!(env, Error::ArithmeticOverflow));
+        let match_amount = wanted
+            .min(pool.cap - already_matched)
+            .min(pool.balance)
+            .min(still_needed);
+        if match_amount <= 0 {
+            continue;
+        }
+
+        pool.balance -= match_amount;
+        if pool.balance == 0 {
+            deactivate_pool(env, &mut pool);
+        }
+        env.storage().persistent().set(&pool_key, &pool);
+        env.storage()
+            .persistent()
+            .set(&matched_key, &(already_matched + match_amount));
+
+        let sponsor_key = DataKey::Donation(request.id, pool.sponsor.clone());
+        let sponsor_donation: i128 = env.storage().persistent().get(&sponsor_key).unwrap_or(0);
+        let new_sponsor_donation = sponsor_donation
+            .checked_add(match_amount)
+            .unwrap_or_else(|| panic_with_error!(env, Error::ArithmeticOverflow));
+        env.storage()
+            .persistent()
+            .set(&sponsor_key, &new_sponsor_donation);
+        index_donor(env, request.id, &pool.sponsor);
+
+        request.raised_amount = request
+            .raised_amount
+            .checked_add(match_amount)
+            .unwrap_or_else(|| panic_with_error!(env, Error::ArithmeticOverflow));
+        matched_value += record_value(env, request.id, &request.token, match_amount);
+        matches.set(pool_id, match_amount);
+
+        MatchApplied {
+            pool_id,
+            request_id: request.id,
+            donor: donor.cloned(),
+            amount: match_amount,
+        }
+        .publish(env);
+    }
+
+    (matches, matched_value)
+}
+
+/// Amount of the primary token the request still needs to reach its
+/// goal. Requests with a valued goal need nothing more once it is
+/// reached and are otherwise not limited.
+pub fn still_needed(env: &Env, request: &HelpRequest) -> i128 {
+    if env
+        .storage()
+        .persistent()
+        .has(&DataKey::ValuedGoal(request.id))
+    {
+        return if requests::goal_reached(env, request) {
+            0
+        } else {
+            i128::MAX
+        };
+    }
+
+    request.goal.saturating_sub(request.raised_amount)
+}
+
+/// Index a pool is listed under while active: its request for pools
+/// dedicated to one request, its token otherwise.
+pub fn pool_index_key(pool: &MatchingPool) -> MatchingKey {
+    match pool.eligibility {
+        MatchEligibility::Request(request_id) => MatchingKey::RequestPools(request_id),
+        _ => MatchingKey::TokenPools(pool.token.clone()),
+    }
+}
+
+pub fn deactivate_pool(env: &Env, pool: &mut MatchingPool) {
+    pool.is_active = false;
+
+    let index_key = pool_index_key(pool);
+    let active: Vec<u64> = env
+        .storage()
+        .persistent()
+        .get(&index_key)
+        .unwrap_or(Vec::new(env));
+    let mut remaining = Vec::new(env);
+    for id in active.iter() {
+        if id != pool.id {
+            remaining.push_back(id);
+        }
+    }
+    env.storage().persistent().set(&index_key, &remaining);
+}
+
+/// Add the reference-currency value of `amount` of `token` to the
+/// request's valued total and return it. No-op returning 0 for requests
+/// without a valued goal.
+pub fn record_value(env: &Env, request_id: u64, token: &Address, amount: i128) -> i128 {
+    if !env
+        .storage()
+        .persistent()
+        .has(&DataKey::ValuedGoal(request_id))
+    {
+        return 0;
+    }
+
+    let value = value_of(env, token, amount);
+
+    let valued_key = DataKey::ValuedRaised(request_id);
+    let valued_raised: i128 = env.storage().persistent().get(&valued_key).unwrap_or(0);
+    let new_valued_raised = valued_raised
+        .checked_add(value)
+        .unwrap_or_else(|| panic_with_error!(env, Error::ArithmeticOverflow));
+    env.storage()
+        .persistent()
+        .set(&valued_key, &new_valued_raised);
+
+    value
+}
+
+/// Value `amount` of `token` in the reference currency using the oracle.
+///
+/// `amount` is divided by `10^decimals()` of the token, so that tokens
+/// with different decimals are valued on the same scale: the result is
+/// expressed with the oracle's decimals.
+///
+/// # Panics
+/// Panics with `Error::PriceUnavailable` if no oracle is configured or it
+/// has no price for `token`, and with `Error::StalePrice` if the latest
+/// price is older than the configured maximum age.
+pub fn value_of(env: &Env, token: &Address, amount: i128) -> i128 {
+    try_value_of(env, token, amount).unwrap_or_else(|error| panic_with_error!(env, error))
+}
+
+/// Same as `value_of`, but returns `Error::PriceUnavailable` or
+/// `Error::StalePrice` instead of panicking when there is no fresh price
+/// for `token`, including when the oracle call itself fails.
+pub fn try_value_of(env: &Env, token: &Address, amount: i128) -> Result<i128, Error> {
+    let oracle: Address = env
+        .storage()
+        .instance()
+        .get(&DataKey::PriceOracle)
+        .ok_or(Error::PriceUnavailable)?;
+    let max_price_age: u64 = env
+        .storage()
+        .instance()
+        .get(&DataKey::MaxPriceAge)
+        .unwrap_or(0);
+
+    let oracle_client = PriceOracleClient::new(env, &oracle);
+    let price = match oracle_client.try_lastprice(&Asset::Stellar(token.clone())) {
+        Ok(Ok(Some(price))) if price.price > 0 => price,
+        _ => return Err(Error::PriceUnavailable),
+    };
+
+    if env.ledger().timestamp().saturating_sub(price.timestamp) > max_price_age {
+        return Err(Error::StalePrice);
+    }
+
+    let scale = 10i128
+        .checked_pow(token::Client::new(env, token).decimals())
+        .unwrap_or_else(|| panic_with_error!(env, Error::ArithmeticOverflow));
+
+    Ok(amount
+        .checked_mul(price.price)
+        .and_then(|v| v.checked_div(scale))
+        .unwrap_or_else(|| panic_with_error!(env, Error::ArithmeticOverflow)))
+}
+
+pub fn donation_grace_period(env: &Env) -> u64 {
+    env.storage()
+        .instance()
+        .get(&DataKey::DonationGracePeriod)
+        .unwrap_or(DEFAULT_DONATION_GRACE_PERIOD)
+}
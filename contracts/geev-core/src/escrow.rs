@@ -0,0 +1,371 @@
+use crate::types::{DataKey, Error, Escrow, EscrowKey, EscrowStatus, EscrowTerms};
+use crate::utils::with_reentrancy_guard;
+use soroban_sdk::{contract, contractevent, contractimpl, panic_with_error, token, Address, Env};
+
+#[contract]
+pub struct EscrowContract;
+
+#[contractevent]
+pub struct EscrowCreated {
+    escrow_id: u64,
+    funder: Address,
+    provider: Address,
+    arbiter: Address,
+    token: Address,
+    amount: i128,
+    request_id: Option<u64>,
+}
+
+#[contractevent]
+pub struct EscrowDelivered {
+    escrow_id: u64,
+    review_deadline: u64,
+}
+
+#[contractevent]
+pub struct EscrowDisputed {
+    escrow_id: u64,
+    by: Address,
+    dispute_deadline: u64,
+}
+
+#[contractevent]
+pub struct EscrowReleased {
+    escrow_id: u64,
+    provider: Address,
+    amount: i128,
+}
+
+#[contractevent]
+pub struct EscrowRefunded {
+    escrow_id: u64,
+    funder: Address,
+    amount: i128,
+}
+
+#[contractevent]
+pub struct EscrowResolved {
+    escrow_id: u64,
+    provider_amount: i128,
+    funder_amount: i128,
+}
+
+#[contractimpl]
+impl EscrowContract {
+    /// Lock `terms.amount` for a provider until the deal is released,
+    /// refunded or resolved by the arbiter.
+    ///
+    /// The provider has `delivery_timeout` seconds to mark the work
+    /// delivered; after that the funder can take the money back.
+    pub fn create_escrow(env: Env, funder: Address, terms: EscrowTerms) -> u64 {
+        funder.require_auth();
+
+        if terms.amount <= 0
+            || terms.delivery_timeout == 0
+            || terms.review_timeout == 0
+            || terms.dispute_timeout == 0
+            || terms.provider == funder
+            || terms.arbiter == funder
+            || terms.arbiter == terms.provider
+        {
+            panic_with_error!(&env, Error::InvalidEscrow);
+        }
+
+        let is_allowed: bool = env
+            .storage()
+            .instance()
+            .get(&DataKey::AllowedToken(terms.token.clone()))
+            .unwrap_or(false);
+        if !is_allowed {
+            panic_with_error!(&env, Error::TokenNotSupported);
+        }
+
+        if let Some(request_id) = terms.request_id {
+            if !env
+                .storage()
+                .persistent()
+                .has(&DataKey::HelpRequest(request_id))
+            {
+                panic_with_error!(&env, Error::HelpRequestNotFound);
+            }
+        }
+
+        let token_client = token::Client::new(&env, &terms.token);
+        token_client.transfer(&funder, env.current_contract_address(), &terms.amount);
+
+        let counter_key = EscrowKey::EscrowCounter;
+        let escrow_id: u64 = env
+            .storage()
+            .instance()
+            .get::<_, u64>(&counter_key)
+            .unwrap_or(0)
+            .checked_add(1)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::ArithmeticOverflow));
+        env.storage().instance().set(&counter_key, &escrow_id);
+
+        let deadline = Self::deadline_after(&env, terms.delivery_timeout);
+        let escrow = Escrow {
+            id: escrow_id,
+            funder: funder.clone(),
+            terms: terms.clone(),
+            status: EscrowStatus::Funded,
+            deadline,
+        };
+        env.storage()
+            .persistent()
+            .set(&EscrowKey::Escrow(escrow_id), &escrow);
+
+        EscrowCreated {
+            escrow_id,
+            funder,
+            provider: terms.provider,
+            arbiter: terms.arbiter,
+            token: terms.token,
+            amount: terms.amount,
+            request_id: terms.request_id,
+        }
+        .publish(&env);
+
+        escrow_id
+    }
+
+    /// Provider marks the work as done. The funder then has
+    /// `review_timeout` seconds to release or dispute before the provider
+    /// can release the payment to themselves.
+    pub fn mark_delivered(env: Env, provider: Address, escrow_id: u64) {
+        provider.require_auth();
+
+        let mut escrow = Self::load(&env, escrow_id);
+
+        if escrow.terms.provider != provider {
+            panic_with_error!(&env, Error::NotEscrowParty);
+        }
+
+        if escrow.status != EscrowStatus::Funded {
+            panic_with_error!(&env, Error::InvalidStatus);
+        }
+
+        if env.ledger().timestamp() > escrow.deadline {
+            panic_with_error!(&env, Error::DeadlinePassed);
+        }
+
+        escrow.status = EscrowStatus::Delivered;
+        escrow.deadline = Self::deadline_after(&env, escrow.terms.review_timeout);
+        env.storage()
+            .persistent()
+            .set(&EscrowKey::Escrow(escrow_id), &escrow);
+
+        EscrowDelivered {
+            escrow_id,
+            review_deadline: escrow.deadline,
+        }
+        .publish(&env);
+    }
+
+    /// Pay the provider in full.
+    ///
+    /// The funder can release at any time before a dispute. The provider can
+    /// release only once the review period after delivery has passed.
+    pub fn release(env: Env, caller: Address, escrow_id: u64) {
+        caller.require_auth();
+
+        with_reentrancy_guard(&env, || {
+            let mut escrow = Self::load(&env, escrow_id);
+
+            if caller == escrow.funder {
+                if escrow.status != EscrowStatus::Funded && escrow.status != EscrowStatus::Delivered
+                {
+                    panic_with_error!(&env, Error::InvalidStatus);
+                }
+            } else if caller == escrow.terms.provider {
+                if escrow.status != EscrowStatus::Delivered {
+                    panic_with_error!(&env, Error::InvalidStatus);
+                }
+                if env.ledger().timestamp() <= escrow.deadline {
+                    panic_with_error!(&env, Error::DeadlineNotReached);
+                }
+            } else {
+                panic_with_error!(&env, Error::NotEscrowParty);
+            }
+
+            escrow.status = EscrowStatus::Released;
+            env.storage()
+                .persistent()
+                .set(&EscrowKey::Escrow(escrow_id), &escrow);
+
+            let token_client = token::Client::new(&env, &escrow.terms.token);
+            token_client.transfer(
+                &env.current_contract_address(),
+                &escrow.terms.provider,
+                &escrow.terms.amount,
+            );
+
+            EscrowReleased {
+                escrow_id,
+                provider: escrow.terms.provider,
+                amount: escrow.terms.amount,
+            }
+            .publish(&env);
+        })
+    }
+
+    /// Return the full amount to the funder.
+    ///
+    /// The provider can refund at any time before a dispute. The funder can
+    /// reclaim the money if the work was not delivered in time, or if the
+    /// arbiter did not resolve a dispute in time.
+    pub fn refund(env: Env, caller: Address, escrow_id: u64) {
+        caller.require_auth();
+
+        with_reentrancy_guard(&env, || {
+            let mut escrow = Self::load(&env, escrow_id);
+
+            if caller == escrow.terms.provider {
+                if escrow.status != EscrowStatus::Funded && escrow.status != EscrowStatus::Delivered
+                {
+                    panic_with_error!(&env, Error::InvalidStatus);
+                }
+            } else if caller == escrow.funder {
+                if escrow.status != EscrowStatus::Funded && escrow.status != EscrowStatus::Disputed
+                {
+                    panic_with_error!(&env, Error::InvalidStatus);
+                }
+                if env.ledger().timestamp() <= escrow.deadline {
+                    panic_with_error!(&env, Error::DeadlineNotReached);
+                }
+            } else {
+                panic_with_error!(&env, Error::NotEscrowParty);
+            }
+
+            escrow.status = EscrowStatus::Refunded;
+            env.storage()
+                .persistent()
+                .set(&EscrowKey::Escrow(escrow_id), &escrow);
+
+            let token_client = token::Client::new(&env, &escrow.terms.token);
+            token_client.transfer(
+                &env.current_contract_address(),
+                &escrow.funder,
+                &escrow.terms.amount,
+            );
+
+            EscrowRefunded {
+                escrow_id,
+                funder: escrow.funder,
+                amount: escrow.terms.amount,
+            }
+            .publish(&env);
+        })
+    }
+
+    /// Either party escalates to the arbiter, who then has
+    /// `dispute_timeout` seconds to resolve. Only possible before the
+    /// current step's deadline, so a late dispute cannot hold up a release
+    /// or refund that is already due.
+    pub fn dispute_escrow(env: Env, caller: Address, escrow_id: u64) {
+        caller.require_auth();
+
+        let mut escrow = Self::load(&env, escrow_id);
+
+        if caller != escrow.funder && caller != escrow.terms.provider {
+            panic_with_error!(&env, Error::NotEscrowParty);
+        }
+
+        if escrow.status != EscrowStatus::Funded && escrow.status != EscrowStatus::Delivered {
+            panic_with_error!(&env, Error::InvalidStatus);
+        }
+
+        if env.ledger().timestamp() > escrow.deadline {
+            panic_with_error!(&env, Error::DeadlinePassed);
+        }
+
+        escrow.status = EscrowStatus::Disputed;
+        escrow.deadline = Self::deadline_after(&env, escrow.terms.dispute_timeout);
+        env.storage()
+            .persistent()
+            .set(&EscrowKey::Escrow(escrow_id), &escrow);
+
+        EscrowDisputed {
+            escrow_id,
+            by: caller,
+            dispute_deadline: escrow.deadline,
+        }
+        .publish(&env);
+    }
+
+    /// Arbiter settles a dispute by paying `provider_amount` to the provider
+    /// and the rest back to the funder. Passing the full amount releases the
+    /// escrow, 0 refunds it, anything in between splits it.
+    pub fn resolve_escrow(env: Env, arbiter: Address, escrow_id: u64, provider_amount: i128) {
+        arbiter.require_auth();
+
+        with_reentrancy_guard(&env, || {
+            let mut escrow = Self::load(&env, escrow_id);
+
+            if escrow.terms.arbiter != arbiter {
+                panic_with_error!(&env, Error::NotEscrowParty);
+            }
+
+            if escrow.status != EscrowStatus::Disputed {
+                panic_with_error!(&env, Error::InvalidStatus);
+            }
+
+            if env.ledger().timestamp() > escrow.deadline {
+                panic_with_error!(&env, Error::DeadlinePassed);
+            }
+
+            if provider_amount < 0 || provider_amount > escrow.terms.amount {
+                panic_with_error!(&env, Error::InvalidEscrow);
+            }
+            let funder_amount = escrow.terms.amount - provider_amount;
+
+            escrow.status = EscrowStatus::Resolved;
+            env.storage()
+                .persistent()
+                .set(&EscrowKey::Escrow(escrow_id), &escrow);
+
+            let token_client = token::Client::new(&env, &escrow.terms.token);
+            if provider_amount > 0 {
+                token_client.transfer(
+                    &env.current_contract_address(),
+                    &escrow.terms.provider,
+                    &provider_amount,
+                );
+            }
+            if funder_amount > 0 {
+                token_client.transfer(
+                    &env.current_contract_address(),
+                    &escrow.funder,
+                    &funder_amount,
+                );
+            }
+
+            EscrowResolved {
+                escrow_id,
+                provider_amount,
+                funder_amount,
+            }
+            .publish(&env);
+        })
+    }
+
+    pub fn get_escrow(env: Env, escrow_id: u64) -> Option<Escrow> {
+        env.storage()
+            .persistent()
+            .get(&EscrowKey::Escrow(escrow_id))
+    }
+
+    fn load(env: &Env, escrow_id: u64) -> Escrow {
+        env.storage()
+            .persistent()
+            .get(&EscrowKey::Escrow(escrow_id))
+            .unwrap_or_else(|| panic_with_error!(env, Error::EscrowNotFound))
+    }
+
+    fn deadline_after(env: &Env, timeout: u64) -> u64 {
+        env.ledger()
+            .timestamp()
+            .checked_add(timeout)
+            .unwrap_or_else(|| panic_with_error!(env, Error::ArithmeticOverflow))
+    }
+}
@@ -1,4 +1,5 @@
-use crate::types::{DataKey, Error, Giveaway, GiveawayStatus};
+use crate::donations;
+use crate::types::{DataKey, Error, Giveaway, GiveawayStatus, LimitKey};
 use crate::utils::with_reentrancy_guard;
 use soroban_sdk::{
     contract, contractevent, contractimpl, panic_with_error, token, Address, Env, String,
@@ -17,6 +18,21 @@ pub struct GiveawayCreated {
     end_time: u64,
 }
 
+#[contractevent]
+pub struct GiveawayCancelled {
+    giveaway_id: u64,
+    creator: Address,
+    refunded: i128,
+}
+
+#[contractevent]
+pub struct PrizeForwarded {
+    giveaway_id: u64,
+    winner: Address,
+    request_id: u64,
+    amount: i128,
+}
+
 #[contractimpl]
 impl GiveawayContract {
     pub fn create_giveaway(
@@ -37,6 +53,18 @@ impl GiveawayContract {
             panic_with_error!(&env, Error::TokenNotSupported);
         }
 
+        let count_key = LimitKey::ActiveGiveawayCount(creator.clone());
+        let active: u32 = env.storage().persistent().get(&count_key).unwrap_or(0);
+        let max_active: u32 = env
+            .storage()
+            .instance()
+            .get(&LimitKey::MaxActiveGiveaways)
+            .unwrap_or(0);
+        if max_active > 0 && active >= max_active {
+            panic_with_error!(&env, Error::CreatorLimitReached);
+        }
+        env.storage().persistent().set(&count_key, &(active + 1));
+
         let token_client = token::Client::new(&env, &token);
         token_client.transfer(&creator, env.current_contract_address(), &amount);
 
@@ -133,27 +161,97 @@ impl GiveawayContract {
         giveaway.winner = Some(winner_address.clone());
         giveaway.status = GiveawayStatus::Claimable;
         env.storage().persistent().set(&giveaway_key, &giveaway);
+        Self::release_slot(&env, &giveaway.creator);
 
         winner_address
     }
 
+    /// Cancel a giveaway nobody has entered and refund the prize to its
+    /// creator, freeing the slot it held under the creator's active limit.
+    pub fn cancel_giveaway(env: Env, creator: Address, giveaway_id: u64) {
+        creator.require_auth();
+
+        let giveaway_key = DataKey::Giveaway(giveaway_id);
+        let mut giveaway: Giveaway = env
+            .storage()
+            .persistent()
+            .get(&giveaway_key)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::GiveawayNotFound));
+
+        if giveaway.creator != creator {
+            panic_with_error!(&env, Error::NotCreator);
+        }
+        if giveaway.status != GiveawayStatus::Active || giveaway.participant_count != 0 {
+            panic_with_error!(&env, Error::InvalidStatus);
+        }
+
+        giveaway.status = GiveawayStatus::Cancelled;
+        env.storage().persistent().set(&giveaway_key, &giveaway);
+        Self::release_slot(&env, &creator);
+
+        let token_client = token::Client::new(&env, &giveaway.token);
+        token_client.transfer(&env.current_contract_address(), &creator, &giveaway.amount);
+
+        GiveawayCancelled {
+            giveaway_id,
+            creator,
+            refunded: giveaway.amount,
+        }
+        .publish(&env);
+    }
+
     pub fn distribute_prize(env: Env, giveaway_id: u64) {
-        with_reentrancy_guard(&env, || {
+        Self::pay_out(&env, giveaway_id, None);
+    }
+
+    /// Winner claims the prize and donates `forward_bps` of it (after fees)
+    /// to a help request in the giveaway's token. The forwarded part stays in
+    /// the contract and is credited to the request as a donation from the
+    /// winner; the rest is sent to the winner.
+    pub fn distribute_prize_with_forward(
+        env: Env,
+        winner: Address,
+        giveaway_id: u64,
+        request_id: u64,
+        forward_bps: u32,
+    ) {
+        winner.require_auth();
+
+        if forward_bps == 0 || forward_bps > 10_000 {
+            panic_with_error!(&env, Error::InvalidForwardShare);
+        }
+
+        let giveaway: Giveaway = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Giveaway(giveaway_id))
+            .unwrap_or_else(|| panic_with_error!(&env, Error::GiveawayNotFound));
+        if giveaway.winner != Some(winner) {
+            panic_with_error!(&env, Error::NotWinner);
+        }
+
+        Self::pay_out(&env, giveaway_id, Some((request_id, forward_bps)));
+    }
+
+    /// Take the platform fee and send the prize to the winner, first routing
+    /// `forward_bps` of it to `request_id` when `forward` is set.
+    fn pay_out(env: &Env, giveaway_id: u64, forward: Option<(u64, u32)>) {
+        with_reentrancy_guard(env, || {
             let giveaway_key = DataKey::Giveaway(giveaway_id);
             let mut giveaway: Giveaway = env
                 .storage()
                 .persistent()
                 .get(&giveaway_key)
-                .unwrap_or_else(|| panic_with_error!(&env, Error::GiveawayNotFound));
+                .unwrap_or_else(|| panic_with_error!(env, Error::GiveawayNotFound));
 
             if giveaway.status != GiveawayStatus::Claimable {
-                panic_with_error!(&env, Error::InvalidStatus);
+                panic_with_error!(env, Error::InvalidStatus);
             }
 
             let winner = giveaway
                 .winner
                 .clone()
-                .unwrap_or_else(|| panic_with_error!(&env, Error::NoParticipants));
+                .unwrap_or_else(|| panic_with_error!(env, Error::NoParticipants));
 
             // 1. Load 'fee_bps' from storage
             let fee_key = DataKey::Fee;
@@ -164,19 +262,53 @@ impl GiveawayContract {
                 .amount
                 .checked_mul(fee_bps as i128)
                 .and_then(|v| v.checked_div(10_000))
-                .unwrap_or_else(|| panic_with_error!(&env, Error::ArithmeticOverflow));
+                .unwrap_or_else(|| panic_with_error!(env, Error::ArithmeticOverflow));
 
             // Calculate net prize
             let net_prize = giveaway
                 .amount
                 .checked_sub(fee_amount)
-                .unwrap_or_else(|| panic_with_error!(&env, Error::ArithmeticOverflow));
+                .unwrap_or_else(|| panic_with_error!(env, Error::ArithmeticOverflow));
+
+            // 3. Credit the forwarded share to the help request
+            let mut winner_prize = net_prize;
+            if let Some((request_id, forward_bps)) = forward {
+                let request = donations::load_donatable(env, request_id);
+                if request.token != giveaway.token {
+                    panic_with_error!(env, Error::TokenNotSupported);
+                }
+
+                let forwarded = net_prize
+                    .checked_mul(forward_bps as i128)
+                    .and_then(|v| v.checked_div(10_000))
+                    .unwrap_or_else(|| panic_with_error!(env, Error::ArithmeticOverflow));
+                if forwarded <= 0 {
+                    panic_with_error!(env, Error::InvalidDonationAmount);
+                }
+                winner_prize = net_prize
+                    .checked_sub(forwarded)
+                    .unwrap_or_else(|| panic_with_error!(env, Error::ArithmeticOverflow));
+
+                // The tokens are already held by the contract, so only the
+                // donation accounting is needed
+                donations::record(env, request, &winner, forwarded, None, None);
+
+                PrizeForwarded {
+                    giveaway_id,
+                    winner: winner.clone(),
+                    request_id,
+                    amount: forwarded,
+                }
+                .publish(env);
+            }
 
-            // 3. Transfer 'net_prize' to Winner
-            let token_client = token::Client::new(&env, &giveaway.token);
-            token_client.transfer(&env.current_contract_address(), &winner, &net_prize);
+            // 4. Transfer the rest of the prize to Winner
+            if winner_prize > 0 {
+                let token_client = token::Client::new(env, &giveaway.token);
+                token_client.transfer(&env.current_contract_address(), &winner, &winner_prize);
+            }
 
-            // 4. Add 'fee_amount' to CollectedFees storage counter
+            // 5. Add 'fee_amount' to CollectedFees storage counter
             let collected_fees_key = DataKey::CollectedFees(giveaway.token.clone());
             let current_fees: i128 = env
                 .storage()
@@ -185,7 +317,7 @@ impl GiveawayContract {
                 .unwrap_or(0);
             let new_fees = current_fees
                 .checked_add(fee_amount)
-                .unwrap_or_else(|| panic_with_error!(&env, Error::ArithmeticOverflow));
+                .unwrap_or_else(|| panic_with_error!(env, Error::ArithmeticOverflow));
             env.storage()
                 .persistent()
                 .set(&collected_fees_key, &new_fees);
@@ -211,6 +343,15 @@ impl GiveawayContract {
         env.storage().instance().set(&fee_key, &fee_bps);
     }
 
+    /// The giveaway no longer counts towards its creator's active limit.
+    fn release_slot(env: &Env, creator: &Address) {
+        let count_key = LimitKey::ActiveGiveawayCount(creator.clone());
+        let active: u32 = env.storage().persistent().get(&count_key).unwrap_or(0);
+        env.storage()
+            .persistent()
+            .set(&count_key, &active.saturating_sub(1));
+    }
+
     fn generate_id(env: &Env) -> u64 {
         let mut counter: u64 = env
             .storage()
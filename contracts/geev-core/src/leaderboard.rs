@@ -0,0 +1,58 @@
+use crate::types::{LeaderboardEntry, LeaderboardKey};
+use soroban_sdk::{Address, Env, Vec};
+
+/// Number of donors shown on each leaderboard.
+pub const LEADERBOARD_SIZE: u32 = 10;
+
+/// Extra entries kept below the visible board, so that when a donor's total
+/// drops the next largest donors move up instead of leaving a gap.
+pub const LEADERBOARD_RESERVE: u32 = 10;
+
+/// Set `donor`'s score on the leaderboard stored under `key`.
+///
+/// The board is kept sorted from largest to smallest amount and holds at
+/// most `LEADERBOARD_SIZE + LEADERBOARD_RESERVE` entries, so updating it
+/// costs a bounded amount of work no matter how many donors there are. A
+/// donor who falls off the reserve is only placed back once their own total
+/// changes again. An amount of 0 or less removes the donor.
+pub fn update(env: &Env, key: &LeaderboardKey, donor: &Address, amount: i128) {
+    let board: Vec<LeaderboardEntry> = env.storage().persistent().get(key).unwrap_or(Vec::new(env));
+
+    let mut updated: Vec<LeaderboardEntry> = Vec::new(env);
+    let mut placed = amount <= 0;
+
+    for entry in board.iter() {
+        if entry.donor == *donor {
+            continue;
+        }
+        if !placed && amount > entry.amount {
+            updated.push_back(LeaderboardEntry {
+                donor: donor.clone(),
+                amount,
+            });
+            placed = true;
+        }
+        updated.push_back(entry);
+    }
+
+    if !placed {
+        updated.push_back(LeaderboardEntry {
+            donor: donor.clone(),
+            amount,
+        });
+    }
+
+    while updated.len() > LEADERBOARD_SIZE + LEADERBOARD_RESERVE {
+        updated.pop_back();
+    }
+
+    env.storage().persistent().set(key, &updated);
+}
+
+/// The first `limit` entries, at most `LEADERBOARD_SIZE`, of the
+/// leaderboard stored under `key`.
+pub fn top(env: &Env, key: &LeaderboardKey, limit: u32) -> Vec<LeaderboardEntry> {
+    let board: Vec<LeaderboardEntry> = env.storage().persistent().get(key).unwrap_or(Vec::new(env));
+
+    board.slice(0..limit.min(LEADERBOARD_SIZE).min(board.len()))
+}
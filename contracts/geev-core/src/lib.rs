@@ -2,9 +2,14 @@
 
 pub mod access;
 pub mod admin;
+pub mod donations;
+pub mod escrow;
 pub mod giveaway;
+pub mod leaderboard;
 pub mod mutual_aid;
+pub mod oracle;
 pub mod profile;
+pub mod requests;
 pub mod types;
 pub mod utils;
 
@@ -13,6 +18,7 @@ mod test;
 
 pub use crate::admin::AdminContract;
 pub use crate::admin::AdminContractClient;
+pub use crate::escrow::{EscrowContract, EscrowContractClient};
 pub use crate::giveaway::GiveawayContract;
 pub use crate::giveaway::GiveawayContractClient;
 pub use crate::mutual_aid::MutualAidContract;
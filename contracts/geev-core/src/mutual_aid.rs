@@ -1,5 +1,40 @@
-use crate::types::{DataKey, Error, HelpRequest, HelpRequestStatus};
-use soroban_sdk::{contract, contractevent, contractimpl, panic_with_error, token, Address, Env};
+use crate::access::check_admin;
+use crate::donations;
+use crate::leaderboard;
+use crate::requests;
+use crate::types::{
+    Contribution, DataKey, DonationNote, Error, FundingRound, HelpRequest, HelpRequestMetadata,
+    HelpRequestRevision, HelpRequestStatus, HelpType, InKindKey, InKindPledge, InKindStatus,
+    LeaderboardEntry, LeaderboardKey, LimitKey, MatchEligibility, MatchingKey, MatchingPool,
+    PledgeKey, PostingBond, ProfileData, RecentDonation, RequestUpdate, RoundKey, RoundStatus,
+    Subscription, SubscriptionKey, UpdateKind, Urgency,
+};
+use crate::utils::{isqrt, with_reentrancy_guard};
+use soroban_sdk::{
+    contract, contractevent, contractimpl, panic_with_error, token, Address, BytesN, Env, String,
+    Vec,
+};
+
+/// Maximum number of entries returned by a single paginated read.
+const MAX_PAGE_SIZE: u32 = 50;
+
+/// Maximum number of help requests that can join one funding round, so that
+/// finalizing it stays within a transaction's budget.
+pub const MAX_ROUND_REQUESTS: u32 = 50;
+
+/// Precision of each request's share of a round's matching amount.
+const ROUND_SHARE_PRECISION: i128 = 1_000_000_000;
+
+/// Default share of the raised amount, in basis points, that donor reports
+/// must represent before a request is frozen.
+const DEFAULT_REPORT_THRESHOLD_BPS: u32 = 3_000;
+
+/// Smallest deposit accepted for a matching pool when the admin has not
+/// set one for the token: one whole unit of a 7-decimal Stellar asset.
+pub const DEFAULT_MIN_POOL_DEPOSIT: i128 = 10_000_000;
+
+/// Moderation reason code recorded when donor reports freeze a request.
+pub const REPORTED_BY_DONORS: u32 = 1;
 
 #[contract]
 pub struct MutualAidContract;
@@ -8,13 +43,138 @@ pub struct MutualAidContract;
 pub struct HelpRequestPosted {
     request_id: u64,
     creator: Address,
+    beneficiary: Option<Address>,
+    goal: i128,
+    help_type: HelpType,
+    urgency: Urgency,
+    content_hash: String,
+}
+
+#[contractevent]
+pub struct HelpRequestUpdated {
+    request_id: u64,
+    revision: u32,
     goal: i128,
+    content_hash: String,
+}
+
+#[contractevent]
+pub struct RequestUpdatePosted {
+    request_id: u64,
+    index: u32,
+    kind: UpdateKind,
+    content_hash: String,
+}
+
+#[contractevent]
+pub struct PledgeDeadlineSet {
+    request_id: u64,
+    deadline: u64,
+}
+
+#[contractevent]
+pub struct PledgeMade {
+    request_id: u64,
+    donor: Address,
+    amount: i128,
+}
+
+#[contractevent]
+pub struct PledgeReleased {
+    request_id: u64,
+    donor: Address,
+    amount: i128,
+}
+
+#[contractevent]
+pub struct SubscriptionCreated {
+    subscription_id: u64,
+    donor: Address,
+    request_id: u64,
+    amount: i128,
+    period: u64,
+    end_time: u64,
+}
+
+#[contractevent]
+pub struct SubscriptionExecuted {
+    subscription_id: u64,
+    amount: i128,
+    next_due: u64,
+}
+
+#[contractevent]
+pub struct SubscriptionPaused {
+    subscription_id: u64,
+}
+
+#[contractevent]
+pub struct SubscriptionResumed {
+    subscription_id: u64,
+}
+
+#[contractevent]
+pub struct SubscriptionCancelled {
+    subscription_id: u64,
+}
+
+#[contractevent]
+pub struct InKindPledged {
+    pledge_id: u64,
+    request_id: u64,
+    helper: Address,
+    description_hash: String,
+    bond: i128,
+    delivery_deadline: u64,
+}
+
+#[contractevent]
+pub struct InKindBondReclaimed {
+    pledge_id: u64,
+    helper: Address,
+    bond: i128,
+}
+
+#[contractevent]
+pub struct DeliveryConfirmed {
+    pledge_id: u64,
+    request_id: u64,
+    helper: Address,
+}
+
+#[contractevent]
+pub struct DeliveryDisputed {
+    pledge_id: u64,
+    request_id: u64,
+}
+
+#[contractevent]
+pub struct DeliveryDisputeResolved {
+    pledge_id: u64,
+    delivered: bool,
+}
+
+#[contractevent]
+pub struct DonationRetracted {
+    request_id: u64,
+    donor: Address,
+    amount: i128,
+}
+
+/// Published instead of `DonationRetracted` for donors who have given
+/// anonymously to the request, carrying their first commitment.
+#[contractevent]
+pub struct AnonymousDonationRetracted {
+    request_id: u64,
+    commitment: BytesN<32>,
+    amount: i128,
 }
 
 #[contractevent]
-pub struct DonationReceived {
+pub struct TokenDonationReceived {
     request_id: u64,
     donor: Address,
+    token: Address,
     amount: i128,
 }
 
@@ -25,20 +185,150 @@ pub struct RefundClaimed {
     amount: i128,
 }
 
+/// Published instead of `RefundClaimed` for anonymous donors.
+#[contractevent]
+pub struct AnonymousRefundClaimed {
+    request_id: u64,
+    commitment: BytesN<32>,
+    amount: i128,
+}
+
+#[contractevent]
+pub struct FundsWithdrawn {
+    request_id: u64,
+    creator: Address,
+    recipient: Address,
+    amount: i128,
+}
+
+#[contractevent]
+pub struct TokenFundsWithdrawn {
+    request_id: u64,
+    creator: Address,
+    recipient: Address,
+    token: Address,
+    amount: i128,
+}
+
+#[contractevent]
+pub struct BeneficiaryConfirmed {
+    request_id: u64,
+    creator: Address,
+    beneficiary: Address,
+}
+
+#[contractevent]
+pub struct TokenRefundClaimed {
+    request_id: u64,
+    donor: Address,
+    token: Address,
+    amount: i128,
+}
+
+/// Published instead of `TokenRefundClaimed` for anonymous donors.
+#[contractevent]
+pub struct AnonymousTokenRefundClaimed {
+    request_id: u64,
+    commitment: BytesN<32>,
+    token: Address,
+    amount: i128,
+}
+
+#[contractevent]
+pub struct MatchingPoolCreated {
+    pool_id: u64,
+    sponsor: Address,
+    token: Address,
+    deposit: i128,
+    ratio_bps: u32,
+    cap: i128,
+}
+
+#[contractevent]
+pub struct MatchingPoolClosed {
+    pool_id: u64,
+    sponsor: Address,
+    refunded: i128,
+}
+
+#[contractevent]
+pub struct RoundCreated {
+    round_id: u64,
+    token: Address,
+    matching_amount: i128,
+    start_time: u64,
+    end_time: u64,
+}
+
+#[contractevent]
+pub struct RoundJoined {
+    round_id: u64,
+    request_id: u64,
+}
+
+#[contractevent]
+pub struct RoundAllocation {
+    round_id: u64,
+    request_id: u64,
+    amount: i128,
+}
+
+#[contractevent]
+pub struct RoundFinalized {
+    round_id: u64,
+    distributed: i128,
+}
+
+#[contractevent]
+pub struct RoundCancelled {
+    round_id: u64,
+    refunded: i128,
+}
+
+#[contractevent]
+pub struct BondLocked {
+    request_id: u64,
+    creator: Address,
+    token: Address,
+    amount: i128,
+}
+
 #[contractevent]
-pub struct RequestCancelled {
+pub struct BondReturned {
     request_id: u64,
     creator: Address,
+    token: Address,
+    amount: i128,
+}
+
+#[contractevent]
+pub struct RequestReported {
+    request_id: u64,
+    donor: Address,
+    reason_hash: String,
+    weight: i128,
 }
 
 #[contractimpl]
 impl MutualAidContract {
+    /// Post a new help request.
+    ///
+    /// `beneficiary` is set when the request is posted on someone else's
+    /// behalf: withdrawals are then paid to the beneficiary, who has to
+    /// `confirm_beneficiary` before the first payout. Editing and cancelling
+    /// stay with the creator.
+    ///
+    /// `metadata` carries the help type, urgency and the content hash of the
+    /// off-chain post, and is echoed in `HelpRequestPosted` so the indexer can
+    /// reconcile the two.
     pub fn post_help_request(
         env: Env,
         creator: Address,
         request_id: u64,
         goal: i128,
         token: Address,
+        beneficiary: Option<Address>,
+        metadata: HelpRequestMetadata,
     ) -> u64 {
         creator.require_auth();
 
@@ -52,33 +342,69 @@ impl MutualAidContract {
             panic_with_error!(&env, Error::HelpRequestAlreadyExists);
         }
 
-        let request = HelpRequest {
+        let count_key = LimitKey::ActiveRequestCount(creator.clone());
+        let active: u32 = env.storage().persistent().get(&count_key).unwrap_or(0);
+        let max_active: u32 = env
+            .storage()
+            .instance()
+            .get(&LimitKey::MaxActiveRequests)
+            .unwrap_or(0);
+        if max_active > 0 && active >= max_active {
+            panic_with_error!(&env, Error::CreatorLimitReached);
+        }
+
+        // Posting for yourself needs no separate confirmation
+        let beneficiary_confirmed = beneficiary.as_ref() == Some(&creator);
+
+        let mut request = HelpRequest {
             id: request_id,
             creator: creator.clone(),
+            beneficiary: beneficiary.clone(),
+            beneficiary_confirmed,
             token,
             goal,
             raised_amount: 0, // ✅ bucket starts empty — no funds locked
-            status: HelpRequestStatus::Open,
+            disbursed_amount: 0,
+            status: HelpRequestStatus::Closed,
             is_verified: false,
+            metadata: metadata.clone(),
+            revision: 0,
+            moderation_reason: None,
         };
+        // Opening the request counts it towards the creator's active limit
+        requests::set_status(&env, &mut request, HelpRequestStatus::Open);
 
         env.storage().persistent().set(&request_key, &request);
+        Self::store_revision(&env, &request);
+        Self::lock_bond(&env, &creator, request_id);
 
         HelpRequestPosted {
             request_id,
             creator,
+            beneficiary,
             goal,
+            help_type: metadata.help_type,
+            urgency: metadata.urgency,
+            content_hash: metadata.content_hash,
         }
         .publish(&env);
 
         request_id
     }
-    pub fn donate(env: Env, donor: Address, request_id: u64, amount: i128) {
-        donor.require_auth();
 
-        if amount <= 0 {
-            panic_with_error!(&env, Error::InvalidDonationAmount);
-        }
+    /// Edit an open request's goal and content hash.
+    ///
+    /// The goal can never drop below what has already been raised. Every edit
+    /// bumps `revision` and is kept in the request's revision history; if the
+    /// new goal is already met the request becomes `FullyFunded` right away.
+    pub fn update_help_request(
+        env: Env,
+        creator: Address,
+        request_id: u64,
+        goal: i128,
+        content_hash: String,
+    ) {
+        creator.require_auth();
 
         let request_key = DataKey::HelpRequest(request_id);
         let mut request: HelpRequest = env
@@ -87,108 +413,2554 @@ impl MutualAidContract {
             .get(&request_key)
             .unwrap_or_else(|| panic_with_error!(&env, Error::HelpRequestNotFound));
 
-        if request.status == HelpRequestStatus::FullyFunded {
-            panic_with_error!(&env, Error::HelpRequestAlreadyFullyFunded);
+        if request.creator != creator {
+            panic_with_error!(&env, Error::NotCreator);
         }
 
-        if request.status == HelpRequestStatus::Cancelled {
+        if request.status != HelpRequestStatus::Open {
             panic_with_error!(&env, Error::InvalidStatus);
         }
 
-        let token_client = token::Client::new(&env, &request.token);
-
-        token_client.transfer(&donor, env.current_contract_address(), &amount);
-
-        // ✅ Track individual donation for refund logic
-        let donation_key = DataKey::Donation(request_id, donor.clone());
-        let previous_donation: i128 = env.storage().persistent().get(&donation_key).unwrap_or(0);
-        let new_donation = previous_donation
-            .checked_add(amount)
-            .unwrap_or_else(|| panic_with_error!(&env, Error::ArithmeticOverflow));
-        env.storage().persistent().set(&donation_key, &new_donation);
+        if goal <= 0 || goal < request.raised_amount {
+            panic_with_error!(&env, Error::InvalidGoalAmount);
+        }
 
-        // ✅ Explicit overflow check for total raised
-        let new_raised = request
-            .raised_amount
-            .checked_add(amount)
+        request.goal = goal;
+        request.metadata.content_hash = content_hash.clone();
+        request.revision = request
+            .revision
+            .checked_add(1)
             .unwrap_or_else(|| panic_with_error!(&env, Error::ArithmeticOverflow));
 
-        request.raised_amount = new_raised;
-
-        if new_raised >= request.goal {
-            request.status = HelpRequestStatus::FullyFunded;
+        if requests::goal_reached(&env, &request) {
+            requests::set_status(&env, &mut request, HelpRequestStatus::FullyFunded);
         }
 
         env.storage().persistent().set(&request_key, &request);
+        Self::store_revision(&env, &request);
 
-        DonationReceived {
+        HelpRequestUpdated {
             request_id,
-            donor,
-            amount,
+            revision: request.revision,
+            goal,
+            content_hash,
         }
         .publish(&env);
     }
 
-    pub fn claim_refund(env: Env, donor: Address, request_id: u64) {
-        donor.require_auth();
+    /// Append a progress update, spending receipt or thank-you note to the
+    /// request's public update log.
+    pub fn post_update(
+        env: Env,
+        creator: Address,
+        request_id: u64,
+        content_hash: String,
+        kind: UpdateKind,
+    ) -> u32 {
+        creator.require_auth();
 
-        let request_key = DataKey::HelpRequest(request_id);
         let request: HelpRequest = env
             .storage()
             .persistent()
-            .get(&request_key)
+            .get(&DataKey::HelpRequest(request_id))
             .unwrap_or_else(|| panic_with_error!(&env, Error::HelpRequestNotFound));
 
-        if request.status != HelpRequestStatus::Cancelled {
-            panic_with_error!(&env, Error::InvalidStatus);
+        if request.creator != creator {
+            panic_with_error!(&env, Error::NotCreator);
         }
 
-        let donation_key = DataKey::Donation(request_id, donor.clone());
-        let amount: i128 = env.storage().persistent().get(&donation_key).unwrap_or(0);
-
-        if amount <= 0 {
-            panic_with_error!(&env, Error::InvalidDonationAmount);
-        }
+        let count_key = DataKey::RequestUpdateCount(request_id);
+        let index: u32 = env.storage().persistent().get(&count_key).unwrap_or(0);
 
-        let token_client = token::Client::new(&env, &request.token);
-        token_client.transfer(&env.current_contract_address(), &donor, &amount);
+        let update = RequestUpdate {
+            index,
+            kind,
+            content_hash: content_hash.clone(),
+            posted_at: env.ledger().timestamp(),
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::RequestUpdate(request_id, index), &update);
+        env.storage().persistent().set(&count_key, &(index + 1));
 
-        // Reset donation amount to prevent double refund
-        env.storage().persistent().set(&donation_key, &0i128);
+        if kind == UpdateKind::Receipt {
+            env.storage()
+                .persistent()
+                .set(&DataKey::HasSpendingReport(request_id), &true);
+        }
 
-        RefundClaimed {
+        RequestUpdatePosted {
             request_id,
-            donor,
-            amount,
+            index,
+            kind,
+            content_hash,
         }
         .publish(&env);
-    }
 
-    pub fn cancel_request(env: Env, creator: Address, request_id: u64) {
-        creator.require_auth();
+        index
+    }
 
-        let request_key = DataKey::HelpRequest(request_id);
-        let mut request: HelpRequest = env
+    /// Read up to `limit` updates (capped at 50) starting at index `start`.
+    pub fn get_updates(env: Env, request_id: u64, start: u32, limit: u32) -> Vec<RequestUpdate> {
+        let count: u32 = env
             .storage()
             .persistent()
-            .get(&request_key)
-            .unwrap_or_else(|| panic_with_error!(&env, Error::HelpRequestNotFound));
+            .get(&DataKey::RequestUpdateCount(request_id))
+            .unwrap_or(0);
+        let end = start.saturating_add(limit.min(MAX_PAGE_SIZE)).min(count);
 
-        if request.creator != creator {
-            panic_with_error!(&env, Error::NotCreator);
+        let mut updates = Vec::new(&env);
+        for index in start..end {
+            if let Some(update) = env
+                .storage()
+                .persistent()
+                .get(&DataKey::RequestUpdate(request_id, index))
+            {
+                updates.push_back(update);
+            }
         }
+        updates
+    }
 
-        if request.status != HelpRequestStatus::Open {
-            panic_with_error!(&env, Error::InvalidStatus);
+    /// Read up to `limit` contributions (capped at 50) to the request,
+    /// starting at index `start`, in the order they were made.
+    pub fn get_contributions(
+        env: Env,
+        request_id: u64,
+        start: u32,
+        limit: u32,
+    ) -> Vec<Contribution> {
+        let count: u32 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::ContributionCount(request_id))
+            .unwrap_or(0);
+        let end = start.saturating_add(limit.min(MAX_PAGE_SIZE)).min(count);
+
+        let mut contributions = Vec::new(&env);
+        for index in start..end {
+            if let Some(contribution) = env
+                .storage()
+                .persistent()
+                .get(&DataKey::Contribution(request_id, index))
+            {
+                contributions.push_back(contribution);
+            }
         }
+        contributions
+    }
 
-        request.status = HelpRequestStatus::Cancelled;
-        env.storage().persistent().set(&request_key, &request);
+    pub fn get_contribution_count(env: Env, request_id: u64) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::ContributionCount(request_id))
+            .unwrap_or(0)
+    }
 
-        RequestCancelled {
-            request_id,
-            creator,
+    pub fn get_update_count(env: Env, request_id: u64) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::RequestUpdateCount(request_id))
+            .unwrap_or(0)
+    }
+
+    /// Whether the creator has posted at least one spending receipt, for
+    /// admins weighing `toggle_request_verification`.
+    pub fn has_spending_report(env: Env, request_id: u64) -> bool {
+        env.storage()
+            .persistent()
+            .get(&DataKey::HasSpendingReport(request_id))
+            .unwrap_or(false)
+    }
+
+    pub fn get_revision(env: Env, request_id: u64, revision: u32) -> Option<HelpRequestRevision> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::HelpRequestRevision(request_id, revision))
+    }
+
+    pub fn get_help_request(env: Env, request_id: u64) -> Option<HelpRequest> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::HelpRequest(request_id))
+    }
+
+    /// Donate `amount` of the request's token.
+    ///
+    /// With an `anonymous_salt`, this contract's events carry
+    /// `sha256(donor XDR || salt)` instead of the donor's address and the
+    /// donor is left off the leaderboards. This is not full anonymity: the
+    /// donation is still stored against the donor's address so refunds
+    /// work, and the token transfer event names the donor.
+    ///
+    /// An optional `note` carries a message hash and a "dedicated to" line,
+    /// stored with this contribution and emitted with the donation event.
+    pub fn donate(
+        env: Env,
+        donor: Address,
+        request_id: u64,
+        amount: i128,
+        anonymous_salt: Option<BytesN<32>>,
+        note: Option<DonationNote>,
+    ) {
+        donor.require_auth();
+
+        Self::receive_donation(&env, &donor, request_id, amount, anonymous_salt, note);
+    }
+
+    /// Donate to several help requests at once.
+    ///
+    /// Every request in the basket must share the token of the first valid
+    /// one, so the total is pulled from the donor in a single transfer. Each
+    /// entry is accounted for as a separate `donate`. Entries that are not
+    /// Open, use another token or have a non-positive amount abort the whole
+    /// basket, or are skipped when `skip_invalid` is set. Returns the total
+    /// donated.
+    pub fn donate_many(
+        env: Env,
+        donor: Address,
+        donations: Vec<(u64, i128)>,
+        skip_invalid: bool,
+    ) -> i128 {
+        donor.require_auth();
+
+        if donations.is_empty() {
+            panic_with_error!(&env, Error::InvalidDonationAmount);
+        }
+
+        let mut basket_token: Option<Address> = None;
+        let mut total: i128 = 0;
+
+        for (request_id, amount) in donations.iter() {
+            let checked = match env
+                .storage()
+                .persistent()
+                .get::<_, HelpRequest>(&DataKey::HelpRequest(request_id))
+            {
+                None => Err(Error::HelpRequestNotFound),
+                Some(_) if amount <= 0 => Err(Error::InvalidDonationAmount),
+                Some(request) if request.status == HelpRequestStatus::FullyFunded => {
+                    Err(Error::HelpRequestAlreadyFullyFunded)
+                }
+                Some(request) if request.status != HelpRequestStatus::Open => {
+                    Err(Error::InvalidStatus)
+                }
+                Some(request) if basket_token.as_ref().is_some_and(|t| *t != request.token) => {
+                    Err(Error::TokenNotSupported)
+                }
+                Some(request) => Ok(request),
+            };
+
+            let request = match checked {
+                Ok(request) => request,
+                Err(_) if skip_invalid => continue,
+                Err(error) => panic_with_error!(&env, error),
+            };
+
+            if basket_token.is_none() {
+                basket_token = Some(request.token.clone());
+            }
+
+            total = total
+                .checked_add(amount)
+                .unwrap_or_else(|| panic_with_error!(&env, Error::ArithmeticOverflow));
+            donations::record(&env, request, &donor, amount, None, None);
+        }
+
+        if let Some(token) = basket_token {
+            let token_client = token::Client::new(&env, &token);
+            token_client.transfer(&donor, env.current_contract_address(), &total);
+        }
+
+        total
+    }
+
+    /// Donate `amount` of any whitelisted `token` to a help request.
+    ///
+    /// Donations in the request's primary `token` go through the regular
+    /// `donate` accounting and count towards the goal. Other tokens are kept
+    /// in separate per-token balances that the creator withdraws and donors
+    /// get refunded from independently, via `withdraw_token` and
+    /// `claim_token_refund`.
+    pub fn donate_token(env: Env, donor: Address, request_id: u64, token: Address, amount: i128) {
+        donor.require_auth();
+
+        let request_key = DataKey::HelpRequest(request_id);
+        let mut request: HelpRequest = env
+            .storage()
+            .persistent()
+            .get(&request_key)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::HelpRequestNotFound));
+
+        if token == request.token {
+            Self::receive_donation(&env, &donor, request_id, amount, None, None);
+            return;
+        }
+
+        if amount <= 0 {
+            panic_with_error!(&env, Error::InvalidDonationAmount);
+        }
+
+        if request.status == HelpRequestStatus::FullyFunded {
+            panic_with_error!(&env, Error::HelpRequestAlreadyFullyFunded);
+        }
+
+        if request.status != HelpRequestStatus::Open {
+            panic_with_error!(&env, Error::InvalidStatus);
+        }
+
+        let is_allowed: bool = env
+            .storage()
+            .instance()
+            .get(&DataKey::AllowedToken(token.clone()))
+            .unwrap_or(false);
+        if !is_allowed {
+            panic_with_error!(&env, Error::TokenNotSupported);
+        }
+
+        let token_client = token::Client::new(&env, &token);
+        token_client.transfer(&donor, env.current_contract_address(), &amount);
+
+        let donation_key = DataKey::TokenDonation(request_id, token.clone(), donor.clone());
+        let previous_donation: i128 = env.storage().persistent().get(&donation_key).unwrap_or(0);
+        let new_donation = previous_donation
+            .checked_add(amount)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::ArithmeticOverflow));
+        env.storage().persistent().set(&donation_key, &new_donation);
+        donations::index_donor(&env, request_id, &donor);
+        donations::record_token_giving(&env, &token, &donor, amount);
+
+        let raised_key = DataKey::TokenRaised(request_id, token.clone());
+        let raised: i128 = env.storage().persistent().get(&raised_key).unwrap_or(0);
+        if raised == 0 {
+            let tokens_key = DataKey::RequestTokens(request_id);
+            let mut tokens: Vec<Address> = env
+                .storage()
+                .persistent()
+                .get(&tokens_key)
+                .unwrap_or(Vec::new(&env));
+            if !tokens.contains(&token) {
+                tokens.push_back(token.clone());
+                env.storage().persistent().set(&tokens_key, &tokens);
+            }
+        }
+        let new_raised = raised
+            .checked_add(amount)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::ArithmeticOverflow));
+        env.storage().persistent().set(&raised_key, &new_raised);
+
+        donations::record_value(&env, request_id, &token, amount);
+        if requests::goal_reached(&env, &request) {
+            requests::set_status(&env, &mut request, HelpRequestStatus::FullyFunded);
+            env.storage().persistent().set(&request_key, &request);
+        }
+
+        TokenDonationReceived {
+            request_id,
+            donor,
+            token,
+            amount,
+        }
+        .publish(&env);
+    }
+
+    /// Pull back the donor's donations that are still within the grace
+    /// period.
+    ///
+    /// Only possible while the request is `Open` or `FullyFunded` and nothing
+    /// has been withdrawn yet, in any token, and only while no funding round
+    /// that counted one of those donations has been finalized. Everything the
+    /// donations triggered is unwound: pool matches go back to their pools,
+    /// their reference-currency value and funding-round contributions are
+    /// removed, and a request that is no longer at its goal reopens.
+    ///
+    /// A donor who has reported the request can no longer retract, so the
+    /// weight of their report stays backed by their donation.
+    pub fn retract_donation(env: Env, donor: Address, request_id: u64) {
+        donor.require_auth();
+
+        with_reentrancy_guard(&env, || {
+            let request_key = DataKey::HelpRequest(request_id);
+            let mut request: HelpRequest = env
+                .storage()
+                .persistent()
+                .get(&request_key)
+                .unwrap_or_else(|| panic_with_error!(&env, Error::HelpRequestNotFound));
+
+            if (request.status != HelpRequestStatus::Open
+                && request.status != HelpRequestStatus::FullyFunded)
+                || Self::has_disbursed(&env, &request)
+            {
+                panic_with_error!(&env, Error::InvalidStatus);
+            }
+
+            if env
+                .storage()
+                .persistent()
+                .has(&DataKey::Report(request_id, donor.clone()))
+            {
+                panic_with_error!(&env, Error::AlreadyReported);
+            }
+
+            let recent_key = DataKey::RecentDonation(request_id, donor.clone());
+            let recent_donations: Vec<RecentDonation> = env
+                .storage()
+                .persistent()
+                .get(&recent_key)
+                .unwrap_or(Vec::new(&env));
+
+            let grace_period = donations::donation_grace_period(&env);
+            let now = env.ledger().timestamp();
+
+            let mut retracted: i128 = 0;
+            for recent in recent_donations.iter() {
+                if now > recent.donated_at.saturating_add(grace_period) {
+                    continue;
+                }
+                Self::unwind_donation(&env, &mut request, &donor, &recent);
+                retracted += recent.amount;
+            }
+
+            if retracted == 0 {
+                panic_with_error!(&env, Error::GracePeriodExpired);
+            }
+
+            if request.status == HelpRequestStatus::FullyFunded
+                && !requests::goal_reached(&env, &request)
+            {
+                requests::set_status(&env, &mut request, HelpRequestStatus::Open);
+            }
+
+            env.storage().persistent().set(&request_key, &request);
+            env.storage().persistent().remove(&recent_key);
+
+            let token_client = token::Client::new(&env, &request.token);
+            token_client.transfer(&env.current_contract_address(), &donor, &retracted);
+
+            match donations::donor_commitment(&env, request_id, &donor) {
+                Some(commitment) => AnonymousDonationRetracted {
+                    request_id,
+                    commitment,
+                    amount: retracted,
+                }
+                .publish(&env),
+                None => DonationRetracted {
+                    request_id,
+                    donor,
+                    amount: retracted,
+                }
+                .publish(&env),
+            }
+        })
+    }
+
+    /// Open the request to pledges until `deadline`.
+    ///
+    /// Can only be set once, so donors know the terms they pledge under.
+    pub fn set_pledge_deadline(env: Env, creator: Address, request_id: u64, deadline: u64) {
+        creator.require_auth();
+
+        let request: HelpRequest = env
+            .storage()
+            .persistent()
+            .get(&DataKey::HelpRequest(request_id))
+            .unwrap_or_else(|| panic_with_error!(&env, Error::HelpRequestNotFound));
+
+        if request.creator != creator {
+            panic_with_error!(&env, Error::NotCreator);
+        }
+
+        if request.status != HelpRequestStatus::Open {
+            panic_with_error!(&env, Error::InvalidStatus);
+        }
+
+        let deadline_key = PledgeKey::PledgeDeadline(request_id);
+        if env.storage().persistent().has(&deadline_key) || deadline <= env.ledger().timestamp() {
+            panic_with_error!(&env, Error::PledgingClosed);
+        }
+
+        env.storage().persistent().set(&deadline_key, &deadline);
+
+        PledgeDeadlineSet {
+            request_id,
+            deadline,
+        }
+        .publish(&env);
+    }
+
+    /// Set up a recurring donation of `amount` every `period` seconds until
+    /// `end_time`.
+    ///
+    /// Payments are pulled with `transfer_from`, so the donor must approve
+    /// this contract as a spender of the request's token. The first payment
+    /// is due immediately.
+    pub fn subscribe(
+        env: Env,
+        donor: Address,
+        request_id: u64,
+        amount: i128,
+        period: u64,
+        end_time: u64,
+    ) -> u64 {
+        donor.require_auth();
+
+        if amount <= 0 {
+            panic_with_error!(&env, Error::InvalidDonationAmount);
+        }
+
+        let now = env.ledger().timestamp();
+        if period == 0 || end_time <= now {
+            panic_with_error!(&env, Error::InvalidSubscription);
+        }
+
+        let request: HelpRequest = env
+            .storage()
+            .persistent()
+            .get(&DataKey::HelpRequest(request_id))
+            .unwrap_or_else(|| panic_with_error!(&env, Error::HelpRequestNotFound));
+
+        if request.status != HelpRequestStatus::Open {
+            panic_with_error!(&env, Error::InvalidStatus);
+        }
+
+        let counter_key = SubscriptionKey::SubscriptionCounter;
+        let subscription_id: u64 = env
+            .storage()
+            .instance()
+            .get::<_, u64>(&counter_key)
+            .unwrap_or(0)
+            .checked_add(1)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::ArithmeticOverflow));
+        env.storage().instance().set(&counter_key, &subscription_id);
+
+        let subscription = Subscription {
+            id: subscription_id,
+            donor: donor.clone(),
+            request_id,
+            amount,
+            period,
+            end_time,
+            next_due: now,
+            is_paused: false,
+        };
+        env.storage().persistent().set(
+            &SubscriptionKey::Subscription(subscription_id),
+            &subscription,
+        );
+
+        SubscriptionCreated {
+            subscription_id,
+            donor,
+            request_id,
+            amount,
+            period,
+            end_time,
+        }
+        .publish(&env);
+
+        subscription_id
+    }
+
+    /// Collect the payment currently due on a subscription.
+    ///
+    /// Permissionless, so any keeper can run it once per period. If the
+    /// request no longer accepts donations or the pull fails (for example
+    /// because the allowance or balance ran out) the subscription is paused
+    /// instead of the call failing. Returns whether a donation was made.
+    pub fn execute_subscription(env: Env, subscription_id: u64) -> bool {
+        let subscription_key = SubscriptionKey::Subscription(subscription_id);
+        let mut subscription: Subscription = env
+            .storage()
+            .persistent()
+            .get(&subscription_key)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::SubscriptionNotFound));
+
+        if subscription.is_paused {
+            panic_with_error!(&env, Error::InvalidStatus);
+        }
+
+        let now = env.ledger().timestamp();
+        if now > subscription.end_time {
+            panic_with_error!(&env, Error::InvalidSubscription);
+        }
+        if now < subscription.next_due {
+            panic_with_error!(&env, Error::SubscriptionNotDue);
+        }
+
+        let request: HelpRequest = env
+            .storage()
+            .persistent()
+            .get(&DataKey::HelpRequest(subscription.request_id))
+            .unwrap_or_else(|| panic_with_error!(&env, Error::HelpRequestNotFound));
+
+        let pulled = request.status == HelpRequestStatus::Open && {
+            let token_client = token::Client::new(&env, &request.token);
+            let contract = env.current_contract_address();
+            matches!(
+                token_client.try_transfer_from(
+                    &contract,
+                    &subscription.donor,
+                    &contract,
+                    &subscription.amount,
+                ),
+                Ok(Ok(()))
+            )
+        };
+
+        if !pulled {
+            subscription.is_paused = true;
+            env.storage()
+                .persistent()
+                .set(&subscription_key, &subscription);
+
+            SubscriptionPaused { subscription_id }.publish(&env);
+            return false;
+        }
+
+        // Skip any periods the keepers missed rather than collecting them
+        let missed = (now - subscription.next_due) / subscription.period;
+        subscription.next_due = (missed + 1)
+            .checked_mul(subscription.period)
+            .and_then(|skipped| subscription.next_due.checked_add(skipped))
+            .unwrap_or_else(|| panic_with_error!(&env, Error::ArithmeticOverflow));
+        env.storage()
+            .persistent()
+            .set(&subscription_key, &subscription);
+
+        donations::record(
+            &env,
+            request,
+            &subscription.donor,
+            subscription.amount,
+            None,
+            None,
+        );
+
+        SubscriptionExecuted {
+            subscription_id,
+            amount: subscription.amount,
+            next_due: subscription.next_due,
+        }
+        .publish(&env);
+
+        true
+    }
+
+    /// Resume a paused subscription once the donor has fixed the allowance.
+    pub fn resume_subscription(env: Env, donor: Address, subscription_id: u64) {
+        donor.require_auth();
+
+        let subscription_key = SubscriptionKey::Subscription(subscription_id);
+        let mut subscription: Subscription = env
+            .storage()
+            .persistent()
+            .get(&subscription_key)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::SubscriptionNotFound));
+
+        if subscription.donor != donor {
+            panic_with_error!(&env, Error::InvalidSubscription);
+        }
+
+        if !subscription.is_paused {
+            panic_with_error!(&env, Error::InvalidStatus);
+        }
+
+        subscription.is_paused = false;
+        env.storage()
+            .persistent()
+            .set(&subscription_key, &subscription);
+
+        SubscriptionResumed { subscription_id }.publish(&env);
+    }
+
+    pub fn cancel_subscription(env: Env, donor: Address, subscription_id: u64) {
+        donor.require_auth();
+
+        let subscription_key = SubscriptionKey::Subscription(subscription_id);
+        let subscription: Subscription = env
+            .storage()
+            .persistent()
+            .get(&subscription_key)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::SubscriptionNotFound));
+
+        if subscription.donor != donor {
+            panic_with_error!(&env, Error::InvalidSubscription);
+        }
+
+        env.storage().persistent().remove(&subscription_key);
+
+        SubscriptionCancelled { subscription_id }.publish(&env);
+    }
+
+    pub fn get_subscription(env: Env, subscription_id: u64) -> Option<Subscription> {
+        env.storage()
+            .persistent()
+            .get(&SubscriptionKey::Subscription(subscription_id))
+    }
+
+    /// Pledge `amount` that only becomes a donation if the request's goal is
+    /// reached by the pledge deadline.
+    ///
+    /// The pledge is held by the contract apart from `raised_amount` and is
+    /// released back to the donor if the goal is missed.
+    pub fn pledge(env: Env, donor: Address, request_id: u64, amount: i128) {
+        donor.require_auth();
+
+        if amount <= 0 {
+            panic_with_error!(&env, Error::InvalidDonationAmount);
+        }
+
+        let request: HelpRequest = env
+            .storage()
+            .persistent()
+            .get(&DataKey::HelpRequest(request_id))
+            .unwrap_or_else(|| panic_with_error!(&env, Error::HelpRequestNotFound));
+
+        if request.status != HelpRequestStatus::Open {
+            panic_with_error!(&env, Error::InvalidStatus);
+        }
+
+        let deadline: u64 = env
+            .storage()
+            .persistent()
+            .get(&PledgeKey::PledgeDeadline(request_id))
+            .unwrap_or_else(|| panic_with_error!(&env, Error::PledgingClosed));
+        if env.ledger().timestamp() > deadline
+            || env
+                .storage()
+                .persistent()
+                .has(&PledgeKey::PledgeCursor(request_id))
+        {
+            panic_with_error!(&env, Error::PledgingClosed);
+        }
+
+        let token_client = token::Client::new(&env, &request.token);
+        token_client.transfer(&donor, env.current_contract_address(), &amount);
+
+        let pledge_key = PledgeKey::Pledge(request_id, donor.clone());
+        let pledged: i128 = env.storage().persistent().get(&pledge_key).unwrap_or(0);
+        if pledged == 0 {
+            let count_key = PledgeKey::PledgerCount(request_id);
+            let count: u32 = env.storage().persistent().get(&count_key).unwrap_or(0);
+            env.storage()
+                .persistent()
+                .set(&PledgeKey::PledgerIndex(request_id, count), &donor);
+            env.storage().persistent().set(&count_key, &(count + 1));
+        }
+        let new_pledged = pledged
+            .checked_add(amount)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::ArithmeticOverflow));
+        env.storage().persistent().set(&pledge_key, &new_pledged);
+
+        let total_key = PledgeKey::PledgedTotal(request_id);
+        let total: i128 = env
+            .storage()
+            .persistent()
+            .get::<_, i128>(&total_key)
+            .unwrap_or(0)
+            .checked_add(amount)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::ArithmeticOverflow));
+        env.storage().persistent().set(&total_key, &total);
+
+        PledgeMade {
+            request_id,
+            donor,
+            amount,
+        }
+        .publish(&env);
+    }
+
+    /// Convert up to `max_count` pledges into donations.
+    ///
+    /// Permissionless. Settlement starts only if raised and pledged funds
+    /// together cover the goal, valued in the reference currency when the
+    /// request has a valued goal, before the pledge deadline; once started it
+    /// can be continued in later calls even after the deadline. Converted
+    /// pledges are not matched by pools or funding rounds. Returns the number
+    /// of pledges visited; 0 once every pledge has been converted.
+    pub fn settle_pledges(env: Env, request_id: u64, max_count: u32) -> u32 {
+        let request_key = DataKey::HelpRequest(request_id);
+        let mut request: HelpRequest = env
+            .storage()
+            .persistent()
+            .get(&request_key)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::HelpRequestNotFound));
+
+        if request.status != HelpRequestStatus::Open
+            && request.status != HelpRequestStatus::FullyFunded
+        {
+            panic_with_error!(&env, Error::InvalidStatus);
+        }
+
+        let cursor_key = PledgeKey::PledgeCursor(request_id);
+        let cursor: u32 = match env.storage().persistent().get(&cursor_key) {
+            Some(cursor) => cursor,
+            None => {
+                let deadline: u64 = env
+                    .storage()
+                    .persistent()
+                    .get(&PledgeKey::PledgeDeadline(request_id))
+                    .unwrap_or_else(|| panic_with_error!(&env, Error::PledgingClosed));
+                let pledged: i128 = env
+                    .storage()
+                    .persistent()
+                    .get(&PledgeKey::PledgedTotal(request_id))
+                    .unwrap_or(0);
+                let pledged_value = if env
+                    .storage()
+                    .persistent()
+                    .has(&DataKey::ValuedGoal(request_id))
+                {
+                    donations::value_of(&env, &request.token, pledged)
+                } else {
+                    0
+                };
+                if env.ledger().timestamp() > deadline
+                    || !requests::goal_reached_with(&env, &request, pledged, pledged_value)
+                {
+                    panic_with_error!(&env, Error::PledgeConditionNotMet);
+                }
+                0
+            }
+        };
+
+        let pledger_count: u32 = env
+            .storage()
+            .persistent()
+            .get(&PledgeKey::PledgerCount(request_id))
+            .unwrap_or(0);
+        let end = cursor.saturating_add(max_count).min(pledger_count);
+        let mut converted: i128 = 0;
+
+        for index in cursor..end {
+            let donor: Address = env
+                .storage()
+                .persistent()
+                .get(&PledgeKey::PledgerIndex(request_id, index))
+                .unwrap_or_else(|| panic_with_error!(&env, Error::InvalidIndex));
+
+            let pledge_key = PledgeKey::Pledge(request_id, donor.clone());
+            let amount: i128 = env.storage().persistent().get(&pledge_key).unwrap_or(0);
+            if amount <= 0 {
+                continue;
+            }
+            env.storage().persistent().remove(&pledge_key);
+            converted += amount;
+
+            donations::record_pledge(&env, &mut request, &donor, amount);
+        }
+
+        let total_key = PledgeKey::PledgedTotal(request_id);
+        let total: i128 = env.storage().persistent().get(&total_key).unwrap_or(0);
+        env.storage()
+            .persistent()
+            .set(&total_key, &(total - converted));
+
+        if request.status == HelpRequestStatus::Open && requests::goal_reached(&env, &request) {
+            requests::set_status(&env, &mut request, HelpRequestStatus::FullyFunded);
+        }
+        env.storage().persistent().set(&request_key, &request);
+        env.storage().persistent().set(&cursor_key, &end);
+
+        end - cursor
+    }
+
+    /// Take back a pledge once the deadline has passed without the goal
+    /// being reached, or at any time after the request was cancelled.
+    pub fn release_pledge(env: Env, donor: Address, request_id: u64) {
+        donor.require_auth();
+
+        let request: HelpRequest = env
+            .storage()
+            .persistent()
+            .get(&DataKey::HelpRequest(request_id))
+            .unwrap_or_else(|| panic_with_error!(&env, Error::HelpRequestNotFound));
+
+        if request.status != HelpRequestStatus::Cancelled {
+            let deadline: u64 = env
+                .storage()
+                .persistent()
+                .get(&PledgeKey::PledgeDeadline(request_id))
+                .unwrap_or_else(|| panic_with_error!(&env, Error::PledgingClosed));
+            if env.ledger().timestamp() <= deadline
+                || env
+                    .storage()
+                    .persistent()
+                    .has(&PledgeKey::PledgeCursor(request_id))
+            {
+                panic_with_error!(&env, Error::PledgeConditionNotMet);
+            }
+        }
+
+        let pledge_key = PledgeKey::Pledge(request_id, donor.clone());
+        let amount: i128 = env.storage().persistent().get(&pledge_key).unwrap_or(0);
+        if amount <= 0 {
+            panic_with_error!(&env, Error::InvalidDonationAmount);
+        }
+        env.storage().persistent().remove(&pledge_key);
+
+        let total_key = PledgeKey::PledgedTotal(request_id);
+        let total: i128 = env.storage().persistent().get(&total_key).unwrap_or(0);
+        env.storage()
+            .persistent()
+            .set(&total_key, &(total - amount));
+
+        let token_client = token::Client::new(&env, &request.token);
+        token_client.transfer(&env.current_contract_address(), &donor, &amount);
+
+        PledgeReleased {
+            request_id,
+            donor,
+            amount,
+        }
+        .publish(&env);
+    }
+
+    /// Total still held in pledges, for showing pledged against raised.
+    pub fn get_pledged(env: Env, request_id: u64) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&PledgeKey::PledgedTotal(request_id))
+            .unwrap_or(0)
+    }
+
+    pub fn get_pledge(env: Env, request_id: u64, donor: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&PledgeKey::Pledge(request_id, donor))
+            .unwrap_or(0)
+    }
+
+    /// Offer goods or a service towards a Material or Service request.
+    ///
+    /// An optional good-faith `bond` in the request's token is held until
+    /// the requester confirms delivery. If the requester has neither
+    /// confirmed nor disputed delivery by `delivery_deadline`, the helper can
+    /// take the bond back with `reclaim_in_kind_bond`.
+    pub fn pledge_in_kind(
+        env: Env,
+        helper: Address,
+        request_id: u64,
+        description_hash: String,
+        bond: i128,
+        delivery_deadline: u64,
+    ) -> u64 {
+        helper.require_auth();
+
+        if bond < 0 {
+            panic_with_error!(&env, Error::InvalidBondAmount);
+        }
+
+        if delivery_deadline <= env.ledger().timestamp() {
+            panic_with_error!(&env, Error::DeadlinePassed);
+        }
+
+        let request: HelpRequest = env
+            .storage()
+            .persistent()
+            .get(&DataKey::HelpRequest(request_id))
+            .unwrap_or_else(|| panic_with_error!(&env, Error::HelpRequestNotFound));
+
+        if request.metadata.help_type != HelpType::Material
+            && request.metadata.help_type != HelpType::Service
+        {
+            panic_with_error!(&env, Error::InvalidHelpType);
+        }
+
+        if request.status != HelpRequestStatus::Open
+            && request.status != HelpRequestStatus::FullyFunded
+        {
+            panic_with_error!(&env, Error::InvalidStatus);
+        }
+
+        if bond > 0 {
+            let token_client = token::Client::new(&env, &request.token);
+            token_client.transfer(&helper, env.current_contract_address(), &bond);
+        }
+
+        let counter_key = InKindKey::InKindCounter;
+        let pledge_id: u64 = env
+            .storage()
+            .instance()
+            .get::<_, u64>(&counter_key)
+            .unwrap_or(0)
+            .checked_add(1)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::ArithmeticOverflow));
+        env.storage().instance().set(&counter_key, &pledge_id);
+
+        let pledge = InKindPledge {
+            id: pledge_id,
+            request_id,
+            helper: helper.clone(),
+            description_hash: description_hash.clone(),
+            bond,
+            delivery_deadline,
+            status: InKindStatus::Pending,
+        };
+        env.storage()
+            .persistent()
+            .set(&InKindKey::InKindPledge(pledge_id), &pledge);
+
+        InKindPledged {
+            pledge_id,
+            request_id,
+            helper,
+            description_hash,
+            bond,
+            delivery_deadline,
+        }
+        .publish(&env);
+
+        pledge_id
+    }
+
+    /// Confirm an in-kind pledge was delivered. Returns the helper's bond
+    /// and counts the delivery on the helper's and the recipient's profiles,
+    /// for those who have one.
+    pub fn confirm_delivery(env: Env, creator: Address, pledge_id: u64) {
+        creator.require_auth();
+
+        let (mut pledge, request) = Self::load_in_kind(&env, pledge_id);
+
+        if request.creator != creator {
+            panic_with_error!(&env, Error::NotCreator);
+        }
+
+        if pledge.status != InKindStatus::Pending {
+            panic_with_error!(&env, Error::InvalidStatus);
+        }
+
+        Self::complete_delivery(&env, &mut pledge, &request);
+    }
+
+    /// Report that an in-kind pledge was not delivered as described. The
+    /// bond stays locked until the admin arbitrates.
+    pub fn dispute_delivery(env: Env, creator: Address, pledge_id: u64) {
+        creator.require_auth();
+
+        let (mut pledge, request) = Self::load_in_kind(&env, pledge_id);
+
+        if request.creator != creator {
+            panic_with_error!(&env, Error::NotCreator);
+        }
+
+        if pledge.status != InKindStatus::Pending {
+            panic_with_error!(&env, Error::InvalidStatus);
+        }
+
+        pledge.status = InKindStatus::Disputed;
+        env.storage()
+            .persistent()
+            .set(&InKindKey::InKindPledge(pledge_id), &pledge);
+
+        DeliveryDisputed {
+            pledge_id,
+            request_id: pledge.request_id,
+        }
+        .publish(&env);
+    }
+
+    /// Return the bond of a pledge the requester neither confirmed nor
+    /// disputed before its delivery deadline to the helper.
+    ///
+    /// Permissionless, since the bond can only go to the helper. The pledge
+    /// ends as `Expired` and no delivery is counted.
+    pub fn reclaim_in_kind_bond(env: Env, pledge_id: u64) {
+        let (mut pledge, request) = Self::load_in_kind(&env, pledge_id);
+
+        if pledge.status != InKindStatus::Pending {
+            panic_with_error!(&env, Error::InvalidStatus);
+        }
+
+        if env.ledger().timestamp() <= pledge.delivery_deadline {
+            panic_with_error!(&env, Error::DeadlineNotReached);
+        }
+
+        pledge.status = InKindStatus::Expired;
+        env.storage()
+            .persistent()
+            .set(&InKindKey::InKindPledge(pledge_id), &pledge);
+
+        if pledge.bond > 0 {
+            let token_client = token::Client::new(&env, &request.token);
+            token_client.transfer(
+                &env.current_contract_address(),
+                &pledge.helper,
+                &pledge.bond,
+            );
+        }
+
+        InKindBondReclaimed {
+            pledge_id,
+            helper: pledge.helper,
+            bond: pledge.bond,
+        }
+        .publish(&env);
+    }
+
+    /// Arbitrate a disputed in-kind pledge - callable only by Admin
+    ///
+    /// If the help was `delivered` the pledge completes as if confirmed.
+    /// Otherwise it fails and the bond goes to the requester as compensation.
+    ///
+    /// # Panics
+    /// Panics if called by non-admin address or the pledge is not Disputed
+    pub fn resolve_delivery_dispute(env: Env, pledge_id: u64, delivered: bool) {
+        check_admin(&env);
+
+        let (mut pledge, request) = Self::load_in_kind(&env, pledge_id);
+
+        if pledge.status != InKindStatus::Disputed {
+            panic_with_error!(&env, Error::InvalidStatus);
+        }
+
+        if delivered {
+            Self::complete_delivery(&env, &mut pledge, &request);
+        } else {
+            pledge.status = InKindStatus::Failed;
+            env.storage()
+                .persistent()
+                .set(&InKindKey::InKindPledge(pledge_id), &pledge);
+
+            if pledge.bond > 0 {
+                let token_client = token::Client::new(&env, &request.token);
+                token_client.transfer(
+                    &env.current_contract_address(),
+                    &request.creator,
+                    &pledge.bond,
+                );
+            }
+        }
+
+        DeliveryDisputeResolved {
+            pledge_id,
+            delivered,
+        }
+        .publish(&env);
+    }
+
+    pub fn get_in_kind_pledge(env: Env, pledge_id: u64) -> Option<InKindPledge> {
+        env.storage()
+            .persistent()
+            .get(&InKindKey::InKindPledge(pledge_id))
+    }
+
+    /// Up to `limit` of the request's largest donors, largest first.
+    pub fn get_top_donors(env: Env, request_id: u64, limit: u32) -> Vec<LeaderboardEntry> {
+        leaderboard::top(&env, &LeaderboardKey::TopDonors(request_id), limit)
+    }
+
+    /// Up to `limit` of the largest donors in `token` across all requests,
+    /// whether it was the request's primary token or a secondary one, net of
+    /// refunds and retractions.
+    pub fn get_top_donors_by_token(env: Env, token: Address, limit: u32) -> Vec<LeaderboardEntry> {
+        leaderboard::top(&env, &LeaderboardKey::TopDonorsByToken(token), limit)
+    }
+
+    pub fn claim_refund(env: Env, donor: Address, request_id: u64) {
+        donor.require_auth();
+
+        let request_key = DataKey::HelpRequest(request_id);
+        let request: HelpRequest = env
+            .storage()
+            .persistent()
+            .get(&request_key)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::HelpRequestNotFound));
+
+        if request.status != HelpRequestStatus::Cancelled {
+            panic_with_error!(&env, Error::InvalidStatus);
+        }
+
+        if !Self::refund_donor(&env, &request, &donor) {
+            panic_with_error!(&env, Error::InvalidDonationAmount);
+        }
+    }
+
+    /// Refund up to `max_count` donors of a cancelled request, without them
+    /// having to call `claim_refund` themselves.
+    ///
+    /// Permissionless. Walks the request's donor index from a stored cursor,
+    /// so repeated calls pick up where the previous one stopped, and refunds
+    /// every token each donor still has a balance in. Returns the number of
+    /// donors visited; 0 once everyone has been processed.
+    pub fn process_refunds(env: Env, request_id: u64, max_count: u32) -> u32 {
+        let request: HelpRequest = env
+            .storage()
+            .persistent()
+            .get(&DataKey::HelpRequest(request_id))
+            .unwrap_or_else(|| panic_with_error!(&env, Error::HelpRequestNotFound));
+
+        if request.status != HelpRequestStatus::Cancelled {
+            panic_with_error!(&env, Error::InvalidStatus);
+        }
+
+        let donor_count: u32 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::DonorCount(request_id))
+            .unwrap_or(0);
+        let cursor_key = DataKey::RefundCursor(request_id);
+        let cursor: u32 = env.storage().persistent().get(&cursor_key).unwrap_or(0);
+        let end = cursor.saturating_add(max_count).min(donor_count);
+
+        let tokens: Vec<Address> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::RequestTokens(request_id))
+            .unwrap_or(Vec::new(&env));
+
+        for index in cursor..end {
+            let donor: Address = env
+                .storage()
+                .persistent()
+                .get(&DataKey::DonorIndex(request_id, index))
+                .unwrap_or_else(|| panic_with_error!(&env, Error::InvalidIndex));
+
+            Self::refund_donor(&env, &request, &donor);
+            for token in tokens.iter() {
+                Self::refund_token_donor(&env, request_id, &token, &donor);
+            }
+        }
+
+        env.storage().persistent().set(&cursor_key, &end);
+
+        end - cursor
+    }
+
+    pub fn cancel_request(env: Env, creator: Address, request_id: u64) {
+        creator.require_auth();
+
+        let request_key = DataKey::HelpRequest(request_id);
+        let mut request: HelpRequest = env
+            .storage()
+            .persistent()
+            .get(&request_key)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::HelpRequestNotFound));
+
+        if request.creator != creator {
+            panic_with_error!(&env, Error::NotCreator);
+        }
+
+        if request.status != HelpRequestStatus::Open {
+            panic_with_error!(&env, Error::InvalidStatus);
+        }
+
+        Self::return_bond(&env, &request);
+        requests::cancel(&env, &mut request);
+    }
+
+    /// Cancel a request that has stayed Open past the admin-configured
+    /// maximum duration.
+    ///
+    /// Permissionless. The request ends exactly as with `cancel_request`:
+    /// donors can claim refunds and the posting bond goes back to the
+    /// creator.
+    pub fn expire_request(env: Env, request_id: u64) {
+        let request_key = DataKey::HelpRequest(request_id);
+        let mut request: HelpRequest = env
+            .storage()
+            .persistent()
+            .get(&request_key)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::HelpRequestNotFound));
+
+        if request.status != HelpRequestStatus::Open {
+            panic_with_error!(&env, Error::InvalidStatus);
+        }
+
+        let max_open: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::MaxOpenDuration)
+            .unwrap_or(0);
+        // Revision 0 is stored when the request is posted
+        let posted_at = env
+            .storage()
+            .persistent()
+            .get::<_, HelpRequestRevision>(&DataKey::HelpRequestRevision(request_id, 0))
+            .map(|revision| revision.updated_at);
+
+        let expired = match posted_at {
+            Some(posted_at) if max_open > 0 => {
+                env.ledger().timestamp() > posted_at.saturating_add(max_open)
+            }
+            _ => false,
+        };
+        if !expired {
+            panic_with_error!(&env, Error::RequestNotExpired);
+        }
+
+        Self::return_bond(&env, &request);
+        requests::cancel(&env, &mut request);
+    }
+
+    /// Report a request as fraudulent or abusive.
+    ///
+    /// Only donors with an outstanding donation can report, once each, and
+    /// each report is weighted by the reporter's donation. When the reported
+    /// weight reaches the configured share of the raised amount the request
+    /// is frozen until the admin resolves the dispute.
+    ///
+    /// Once a price oracle is configured, donations in secondary tokens count
+    /// too, in both the weight and the raised amount, valued in units of the
+    /// request's primary token.
+    pub fn report_request(env: Env, donor: Address, request_id: u64, reason_hash: String) {
+        donor.require_auth();
+
+        let request_key = DataKey::HelpRequest(request_id);
+        let mut request: HelpRequest = env
+            .storage()
+            .persistent()
+            .get(&request_key)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::HelpRequestNotFound));
+
+        if request.status != HelpRequestStatus::Open
+            && request.status != HelpRequestStatus::FullyFunded
+        {
+            panic_with_error!(&env, Error::InvalidStatus);
+        }
+
+        let donation: i128 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Donation(request_id, donor.clone()))
+            .unwrap_or(0);
+        let weight = donation
+            .checked_add(Self::secondary_in_primary_units(
+                &env,
+                &request,
+                Some(&donor),
+            ))
+            .unwrap_or_else(|| panic_with_error!(&env, Error::ArithmeticOverflow));
+        if weight <= 0 {
+            panic_with_error!(&env, Error::InvalidDonationAmount);
+        }
+
+        let report_key = DataKey::Report(request_id, donor.clone());
+        if env.storage().persistent().has(&report_key) {
+            panic_with_error!(&env, Error::AlreadyReported);
+        }
+        env.storage().persistent().set(&report_key, &reason_hash);
+
+        let weight_key = DataKey::ReportWeight(request_id);
+        let reported: i128 = env
+            .storage()
+            .persistent()
+            .get::<_, i128>(&weight_key)
+            .unwrap_or(0)
+            .checked_add(weight)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::ArithmeticOverflow));
+        env.storage().persistent().set(&weight_key, &reported);
+
+        RequestReported {
+            request_id,
+            donor,
+            reason_hash,
+            weight,
+        }
+        .publish(&env);
+
+        let threshold_bps: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::ReportThreshold)
+            .unwrap_or(DEFAULT_REPORT_THRESHOLD_BPS);
+        let raised = request
+            .raised_amount
+            .checked_add(Self::secondary_in_primary_units(&env, &request, None))
+            .unwrap_or_else(|| panic_with_error!(&env, Error::ArithmeticOverflow));
+        let reported_bps = reported
+            .checked_mul(10_000)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::ArithmeticOverflow))
+            / raised.max(1);
+
+        if reported_bps >= threshold_bps as i128 {
+            requests::freeze(&env, &mut request, REPORTED_BY_DONORS);
+        }
+    }
+
+    pub fn get_report_weight(env: Env, request_id: u64) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::ReportWeight(request_id))
+            .unwrap_or(0)
+    }
+
+    /// Accept the beneficiary role on a request posted on your behalf.
+    /// Required before any funds can be withdrawn to the beneficiary.
+    pub fn confirm_beneficiary(env: Env, beneficiary: Address, request_id: u64) {
+        beneficiary.require_auth();
+
+        let request_key = DataKey::HelpRequest(request_id);
+        let mut request: HelpRequest = env
+            .storage()
+            .persistent()
+            .get(&request_key)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::HelpRequestNotFound));
+
+        if request.beneficiary.as_ref() != Some(&beneficiary) {
+            panic_with_error!(&env, Error::NotBeneficiary);
+        }
+
+        request.beneficiary_confirmed = true;
+        env.storage().persistent().set(&request_key, &request);
+
+        BeneficiaryConfirmed {
+            request_id,
+            creator: request.creator,
+            beneficiary,
+        }
+        .publish(&env);
+    }
+
+    /// Pay out part of the raised funds to the request creator, or to the
+    /// beneficiary when the request was posted on someone's behalf.
+    ///
+    /// Withdrawals are allowed while the request is `Open` or `FullyFunded`
+    /// and are tracked in `disbursed_amount`, so that refunds after a later
+    /// cancellation only hand back what the contract still holds. Once a
+    /// fully funded request has been completely paid out it becomes `Closed`,
+    /// unless pledge settlement has started and not yet converted every
+    /// pledge.
+    pub fn withdraw(env: Env, creator: Address, request_id: u64, amount: i128) {
+        creator.require_auth();
+
+        with_reentrancy_guard(&env, || {
+            let request_key = DataKey::HelpRequest(request_id);
+            let mut request: HelpRequest = env
+                .storage()
+                .persistent()
+                .get(&request_key)
+                .unwrap_or_else(|| panic_with_error!(&env, Error::HelpRequestNotFound));
+
+            if request.creator != creator {
+                panic_with_error!(&env, Error::NotCreator);
+            }
+
+            if request.status != HelpRequestStatus::Open
+                && request.status != HelpRequestStatus::FullyFunded
+            {
+                panic_with_error!(&env, Error::InvalidStatus);
+            }
+
+            let available = request
+                .raised_amount
+                .checked_sub(request.disbursed_amount)
+                .unwrap_or_else(|| panic_with_error!(&env, Error::ArithmeticOverflow));
+
+            if amount <= 0 || amount > available {
+                panic_with_error!(&env, Error::InvalidWithdrawalAmount);
+            }
+
+            let recipient = Self::payout_recipient(&env, &request);
+            let token_client = token::Client::new(&env, &request.token);
+            token_client.transfer(&env.current_contract_address(), &recipient, &amount);
+
+            request.disbursed_amount = request
+                .disbursed_amount
+                .checked_add(amount)
+                .unwrap_or_else(|| panic_with_error!(&env, Error::ArithmeticOverflow));
+
+            if request.status == HelpRequestStatus::FullyFunded
+                && request.disbursed_amount == request.raised_amount
+                && !Self::settling_pledges(&env, request_id)
+            {
+                requests::set_status(&env, &mut request, HelpRequestStatus::Closed);
+                Self::return_bond(&env, &request);
+            }
+
+            env.storage().persistent().set(&request_key, &request);
+
+            FundsWithdrawn {
+                request_id,
+                creator,
+                recipient,
+                amount,
+            }
+            .publish(&env);
+        })
+    }
+
+    /// Denominate a request's goal in the oracle's reference currency.
+    ///
+    /// `valued_goal` is expressed with the oracle's `decimals()`, e.g. 10.00
+    /// is `1000` for an oracle reporting two decimals.
+    ///
+    /// Must be set before the request receives any donation. From then on
+    /// every donation, in any token, is valued through the configured price
+    /// oracle and the request becomes `FullyFunded` once the valued total
+    /// reaches `valued_goal`, instead of comparing `raised_amount` to `goal`.
+    pub fn set_valued_goal(env: Env, creator: Address, request_id: u64, valued_goal: i128) {
+        creator.require_auth();
+
+        let request: HelpRequest = env
+            .storage()
+            .persistent()
+            .get(&DataKey::HelpRequest(request_id))
+            .unwrap_or_else(|| panic_with_error!(&env, Error::HelpRequestNotFound));
+
+        if request.creator != creator {
+            panic_with_error!(&env, Error::NotCreator);
+        }
+
+        if valued_goal <= 0 {
+            panic_with_error!(&env, Error::InvalidGoalAmount);
+        }
+
+        if request.status != HelpRequestStatus::Open
+            || request.raised_amount != 0
+            || env
+                .storage()
+                .persistent()
+                .has(&DataKey::RequestTokens(request_id))
+        {
+            panic_with_error!(&env, Error::InvalidStatus);
+        }
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::ValuedGoal(request_id), &valued_goal);
+    }
+
+    /// Total donated to a request in the reference currency, valued at the
+    /// oracle price of each donation at the time it was made.
+    pub fn get_valued_raised(env: Env, request_id: u64) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::ValuedRaised(request_id))
+            .unwrap_or(0)
+    }
+
+    /// Withdraw funds raised in a secondary (non-primary) token.
+    ///
+    /// Works like `withdraw`, including after the primary balance has been
+    /// fully paid out and the request is `Closed`.
+    pub fn withdraw_token(
+        env: Env,
+        creator: Address,
+        request_id: u64,
+        token: Address,
+        amount: i128,
+    ) {
+        creator.require_auth();
+
+        with_reentrancy_guard(&env, || {
+            let request: HelpRequest = env
+                .storage()
+                .persistent()
+                .get(&DataKey::HelpRequest(request_id))
+                .unwrap_or_else(|| panic_with_error!(&env, Error::HelpRequestNotFound));
+
+            if request.creator != creator {
+                panic_with_error!(&env, Error::NotCreator);
+            }
+
+            if request.status != HelpRequestStatus::Open
+                && request.status != HelpRequestStatus::FullyFunded
+                && request.status != HelpRequestStatus::Closed
+            {
+                panic_with_error!(&env, Error::InvalidStatus);
+            }
+
+            if token == request.token {
+                panic_with_error!(&env, Error::TokenNotSupported);
+            }
+
+            let raised: i128 = env
+                .storage()
+                .persistent()
+                .get(&DataKey::TokenRaised(request_id, token.clone()))
+                .unwrap_or(0);
+            let disbursed_key = DataKey::TokenDisbursed(request_id, token.clone());
+            let disbursed: i128 = env.storage().persistent().get(&disbursed_key).unwrap_or(0);
+
+            let available = raised
+                .checked_sub(disbursed)
+                .unwrap_or_else(|| panic_with_error!(&env, Error::ArithmeticOverflow));
+
+            if amount <= 0 || amount > available {
+                panic_with_error!(&env, Error::InvalidWithdrawalAmount);
+            }
+
+            let recipient = Self::payout_recipient(&env, &request);
+            let token_client = token::Client::new(&env, &token);
+            token_client.transfer(&env.current_contract_address(), &recipient, &amount);
+
+            env.storage()
+                .persistent()
+                .set(&disbursed_key, &(disbursed + amount));
+
+            TokenFundsWithdrawn {
+                request_id,
+                creator,
+                recipient,
+                token,
+                amount,
+            }
+            .publish(&env);
+        })
+    }
+
+    /// Refund a donor's balance in a secondary token after cancellation,
+    /// pro-rata to what is left of that token's balance.
+    pub fn claim_token_refund(env: Env, donor: Address, request_id: u64, token: Address) {
+        donor.require_auth();
+
+        let request: HelpRequest = env
+            .storage()
+            .persistent()
+            .get(&DataKey::HelpRequest(request_id))
+            .unwrap_or_else(|| panic_with_error!(&env, Error::HelpRequestNotFound));
+
+        if request.status != HelpRequestStatus::Cancelled {
+            panic_with_error!(&env, Error::InvalidStatus);
+        }
+
+        if !Self::refund_token_donor(&env, request_id, &token, &donor) {
+            panic_with_error!(&env, Error::InvalidDonationAmount);
+        }
+    }
+
+    /// Total raised for a request in `token`. For the primary token this is
+    /// the request's `raised_amount`.
+    pub fn get_token_raised(env: Env, request_id: u64, token: Address) -> i128 {
+        let request: HelpRequest = env
+            .storage()
+            .persistent()
+            .get(&DataKey::HelpRequest(request_id))
+            .unwrap_or_else(|| panic_with_error!(&env, Error::HelpRequestNotFound));
+
+        if token == request.token {
+            return request.raised_amount;
+        }
+
+        env.storage()
+            .persistent()
+            .get(&DataKey::TokenRaised(request_id, token))
+            .unwrap_or(0)
+    }
+
+    /// Open a sponsor matching pool.
+    ///
+    /// The sponsor deposits `deposit` tokens up front. Every eligible donation
+    /// in `token` is then matched at `ratio_bps` (10_000 = 1:1) until either
+    /// the pool runs dry or `cap` has been matched for that request.
+    ///
+    /// `token` must be whitelisted and `deposit` at least the token's minimum
+    /// pool deposit, so that donations do not have to walk spam pools.
+    pub fn create_matching_pool(
+        env: Env,
+        sponsor: Address,
+        token: Address,
+        deposit: i128,
+        ratio_bps: u32,
+        cap: i128,
+        eligibility: MatchEligibility,
+    ) -> u64 {
+        sponsor.require_auth();
+
+        if deposit <= 0 || cap <= 0 || ratio_bps == 0 {
+            panic_with_error!(&env, Error::InvalidMatchingPool);
+        }
+
+        if !env
+            .storage()
+            .instance()
+            .get(&DataKey::AllowedToken(token.clone()))
+            .unwrap_or(false)
+        {
+            panic_with_error!(&env, Error::TokenNotSupported);
+        }
+
+        let min_deposit: i128 = env
+            .storage()
+            .instance()
+            .get(&MatchingKey::MinPoolDeposit(token.clone()))
+            .unwrap_or(DEFAULT_MIN_POOL_DEPOSIT);
+        if deposit < min_deposit {
+            panic_with_error!(&env, Error::InvalidMatchingPool);
+        }
+
+        let token_client = token::Client::new(&env, &token);
+        token_client.transfer(&sponsor, env.current_contract_address(), &deposit);
+
+        let mut pool_id: u64 = env
+            .storage()
+            .instance()
+            .get(&MatchingKey::MatchingPoolCounter)
+            .unwrap_or(0);
+        pool_id += 1;
+        env.storage()
+            .instance()
+            .set(&MatchingKey::MatchingPoolCounter, &pool_id);
+
+        let pool = MatchingPool {
+            id: pool_id,
+            sponsor: sponsor.clone(),
+            token: token.clone(),
+            ratio_bps,
+            cap,
+            balance: deposit,
+            eligibility,
+            is_active: true,
+        };
+        env.storage()
+            .persistent()
+            .set(&MatchingKey::MatchingPool(pool_id), &pool);
+
+        let index_key = donations::pool_index_key(&pool);
+        let mut active: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&index_key)
+            .unwrap_or(Vec::new(&env));
+        active.push_back(pool_id);
+        env.storage().persistent().set(&index_key, &active);
+
+        MatchingPoolCreated {
+            pool_id,
+            sponsor,
+            token,
+            deposit,
+            ratio_bps,
+            cap,
+        }
+        .publish(&env);
+
+        pool_id
+    }
+
+    /// Stop matching and return the unspent pool balance to the sponsor.
+    pub fn close_matching_pool(env: Env, sponsor: Address, pool_id: u64) {
+        sponsor.require_auth();
+
+        let pool_key = MatchingKey::MatchingPool(pool_id);
+        let mut pool: MatchingPool = env
+            .storage()
+            .persistent()
+            .get(&pool_key)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::MatchingPoolNotFound));
+
+        if pool.sponsor != sponsor {
+            panic_with_error!(&env, Error::NotSponsor);
+        }
+
+        if !pool.is_active {
+            panic_with_error!(&env, Error::InvalidStatus);
+        }
+
+        let refunded = pool.balance;
+        if refunded > 0 {
+            let token_client = token::Client::new(&env, &pool.token);
+            token_client.transfer(&env.current_contract_address(), &sponsor, &refunded);
+        }
+
+        pool.balance = 0;
+        donations::deactivate_pool(&env, &mut pool);
+        env.storage().persistent().set(&pool_key, &pool);
+
+        MatchingPoolClosed {
+            pool_id,
+            sponsor,
+            refunded,
+        }
+        .publish(&env);
+    }
+
+    pub fn get_matching_pool(env: Env, pool_id: u64) -> Option<MatchingPool> {
+        env.storage()
+            .persistent()
+            .get(&MatchingKey::MatchingPool(pool_id))
+    }
+
+    /// Open a quadratic-funding round - callable only by Admin
+    ///
+    /// The admin deposits `matching_amount` which is split across the joined
+    /// requests when the round is finalized, in proportion to the square of
+    /// the sum of square roots of each unique donor's contributions made
+    /// between `start_time` and `end_time`.
+    ///
+    /// # Panics
+    /// Panics if called by non-admin address or the token is not whitelisted
+    pub fn create_round(
+        env: Env,
+        token: Address,
+        matching_amount: i128,
+        start_time: u64,
+        end_time: u64,
+        require_profile: bool,
+    ) -> u64 {
+        let admin = check_admin(&env);
+
+        if matching_amount <= 0 || end_time <= start_time {
+            panic_with_error!(&env, Error::InvalidRound);
+        }
+
+        if !env
+            .storage()
+            .instance()
+            .get(&DataKey::AllowedToken(token.clone()))
+            .unwrap_or(false)
+        {
+            panic_with_error!(&env, Error::TokenNotSupported);
+        }
+
+        let token_client = token::Client::new(&env, &token);
+        token_client.transfer(&admin, env.current_contract_address(), &matching_amount);
+
+        let mut round_id: u64 = env
+            .storage()
+            .instance()
+            .get(&RoundKey::RoundCounter)
+            .unwrap_or(0);
+        round_id += 1;
+        env.storage()
+            .instance()
+            .set(&RoundKey::RoundCounter, &round_id);
+
+        let round = FundingRound {
+            id: round_id,
+            funder: admin,
+            token: token.clone(),
+            matching_amount,
+            start_time,
+            end_time,
+            require_profile,
+            status: RoundStatus::Active,
+        };
+        env.storage()
+            .persistent()
+            .set(&RoundKey::FundingRound(round_id), &round);
+
+        RoundCreated {
+            round_id,
+            token,
+            matching_amount,
+            start_time,
+            end_time,
+        }
+        .publish(&env);
+
+        round_id
+    }
+
+    /// Enter an open help request into a funding round. A request can take
+    /// part in one round at a time, and a round takes at most
+    /// `MAX_ROUND_REQUESTS` requests.
+    pub fn join_round(env: Env, creator: Address, round_id: u64, request_id: u64) {
+        creator.require_auth();
+
+        let round: FundingRound = env
+            .storage()
+            .persistent()
+            .get(&RoundKey::FundingRound(round_id))
+            .unwrap_or_else(|| panic_with_error!(&env, Error::RoundNotFound));
+
+        let request: HelpRequest = env
+            .storage()
+            .persistent()
+            .get(&DataKey::HelpRequest(request_id))
+            .unwrap_or_else(|| panic_with_error!(&env, Error::HelpRequestNotFound));
+
+        if request.creator != creator {
+            panic_with_error!(&env, Error::NotCreator);
+        }
+
+        if request.status != HelpRequestStatus::Open {
+            panic_with_error!(&env, Error::InvalidStatus);
+        }
+
+        if round.status != RoundStatus::Active
+            || env.ledger().timestamp() > round.end_time
+            || round.token != request.token
+        {
+            panic_with_error!(&env, Error::InvalidRound);
+        }
+
+        let request_round_key = RoundKey::RequestRound(request_id);
+        if let Some(current) = env.storage().persistent().get::<_, u64>(&request_round_key) {
+            let current_round: FundingRound = env
+                .storage()
+                .persistent()
+                .get(&RoundKey::FundingRound(current))
+                .unwrap_or_else(|| panic_with_error!(&env, Error::RoundNotFound));
+            if current_round.status == RoundStatus::Active {
+                panic_with_error!(&env, Error::InvalidRound);
+            }
+        }
+        env.storage()
+            .persistent()
+            .set(&request_round_key, &round_id);
+
+        let requests_key = RoundKey::RoundRequests(round_id);
+        let mut requests: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&requests_key)
+            .unwrap_or(Vec::new(&env));
+        if requests.len() >= MAX_ROUND_REQUESTS {
+            panic_with_error!(&env, Error::InvalidRound);
+        }
+        requests.push_back(request_id);
+        env.storage().persistent().set(&requests_key, &requests);
+
+        RoundJoined {
+            round_id,
+            request_id,
+        }
+        .publish(&env);
+    }
+
+    /// Distribute the round's matching pool once `end_time` has passed.
+    ///
+    /// Allocations are credited to each request's `raised_amount` and to the
+    /// funder's `DataKey::Donation` entry, so a later cancellation refunds the
+    /// funder like any other donor. Rounding dust and the shares of requests
+    /// that are no longer accepting funds are returned to the funder.
+    pub fn finalize_round(env: Env, round_id: u64) {
+        let round_key = RoundKey::FundingRound(round_id);
+        let mut round: FundingRound = env
+            .storage()
+            .persistent()
+            .get(&round_key)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::RoundNotFound));
+
+        if round.status != RoundStatus::Active {
+            panic_with_error!(&env, Error::InvalidStatus);
+        }
+        if env.ledger().timestamp() <= round.end_time {
+            panic_with_error!(&env, Error::RoundStillActive);
+        }
+
+        let requests: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&RoundKey::RoundRequests(round_id))
+            .unwrap_or(Vec::new(&env));
+
+        let mut sqrt_sums: Vec<i128> = Vec::new(&env);
+        let mut max_sqrt_sum: i128 = 0;
+        for request_id in requests.iter() {
+            let sqrt_sum: i128 = env
+                .storage()
+                .persistent()
+                .get(&RoundKey::RoundSqrtSum(round_id, request_id))
+                .unwrap_or(0);
+            max_sqrt_sum = max_sqrt_sum.max(sqrt_sum);
+            sqrt_sums.push_back(sqrt_sum);
+        }
+
+        // Scale the sums down just enough that every weight, their total and
+        // each weight times ROUND_SHARE_PRECISION fit in an i128. Only the
+        // ratios between weights matter, so this cannot make finalizing fail.
+        let sqrt_sum_limit =
+            isqrt(i128::MAX / (MAX_ROUND_REQUESTS as i128 * ROUND_SHARE_PRECISION));
+        let divisor = max_sqrt_sum / sqrt_sum_limit + 1;
+
+        let mut weights: Vec<i128> = Vec::new(&env);
+        let mut total_weight: i128 = 0;
+        for sqrt_sum in sqrt_sums.iter() {
+            let scaled = sqrt_sum / divisor;
+            let weight = scaled * scaled;
+            total_weight += weight;
+            weights.push_back(weight);
+        }
+
+        let mut distributed: i128 = 0;
+        if total_weight > 0 {
+            for (request_id, weight) in requests.iter().zip(weights.iter()) {
+                let request_key = DataKey::HelpRequest(request_id);
+                let mut request: HelpRequest = match env.storage().persistent().get(&request_key) {
+                    Some(request) => request,
+                    None => continue,
+                };
+                if request.status != HelpRequestStatus::Open
+                    && request.status != HelpRequestStatus::FullyFunded
+                {
+                    continue;
+                }
+
+                let share = weight * ROUND_SHARE_PRECISION / total_weight;
+                let allocation = match round.matching_amount.checked_mul(share) {
+                    Some(scaled) => scaled / ROUND_SHARE_PRECISION,
+                    None => round.matching_amount / ROUND_SHARE_PRECISION * share,
+                };
+                if allocation <= 0 {
+                    continue;
+                }
+
+                let funder_key = DataKey::Donation(request_id, round.funder.clone());
+                let funder_donation: i128 =
+                    env.storage().persistent().get(&funder_key).unwrap_or(0);
+                let new_funder_donation = funder_donation
+                    .checked_add(allocation)
+                    .unwrap_or_else(|| panic_with_error!(&env, Error::ArithmeticOverflow));
+                env.storage()
+                    .persistent()
+                    .set(&funder_key, &new_funder_donation);
+                donations::index_donor(&env, request_id, &round.funder);
+
+                request.raised_amount = request
+                    .raised_amount
+                    .checked_add(allocation)
+                    .unwrap_or_else(|| panic_with_error!(&env, Error::ArithmeticOverflow));
+                donations::record_value(&env, request_id, &request.token, allocation);
+                if requests::goal_reached(&env, &request) {
+                    requests::set_status(&env, &mut request, HelpRequestStatus::FullyFunded);
+                }
+                env.storage().persistent().set(&request_key, &request);
+
+                distributed += allocation;
+
+                RoundAllocation {
+                    round_id,
+                    request_id,
+                    amount: allocation,
+                }
+                .publish(&env);
+            }
+        }
+
+        let leftover = round.matching_amount - distributed;
+        if leftover > 0 {
+            let token_client = token::Client::new(&env, &round.token);
+            token_client.transfer(&env.current_contract_address(), &round.funder, &leftover);
+        }
+
+        round.status = RoundStatus::Finalized;
+        env.storage().persistent().set(&round_key, &round);
+
+        RoundFinalized {
+            round_id,
+            distributed,
+        }
+        .publish(&env);
+    }
+
+    /// Cancel an active round and return its whole matching amount to the
+    /// funder, for rounds that should not or cannot be finalized.
+    pub fn cancel_round(env: Env, round_id: u64) {
+        let round_key = RoundKey::FundingRound(round_id);
+        let mut round: FundingRound = env
+            .storage()
+            .persistent()
+            .get(&round_key)
+            .unwrap_or_else(|| panic_with_error!(&env, Error::RoundNotFound));
+
+        round.funder.require_auth();
+
+        if round.status != RoundStatus::Active {
+            panic_with_error!(&env, Error::InvalidStatus);
+        }
+
+        round.status = RoundStatus::Cancelled;
+        env.storage().persistent().set(&round_key, &round);
+
+        let token_client = token::Client::new(&env, &round.token);
+        token_client.transfer(
+            &env.current_contract_address(),
+            &round.funder,
+            &round.matching_amount,
+        );
+
+        RoundCancelled {
+            round_id,
+            refunded: round.matching_amount,
         }
         .publish(&env);
     }
+
+    pub fn get_round(env: Env, round_id: u64) -> Option<FundingRound> {
+        env.storage()
+            .persistent()
+            .get(&RoundKey::FundingRound(round_id))
+    }
+
+    /// Pull `amount` of the request's primary token from `donor` and run the
+    /// donation accounting: per-donor totals, funding rounds, matching pools
+    /// and the `FullyFunded` transition.
+    fn receive_donation(
+        env: &Env,
+        donor: &Address,
+        request_id: u64,
+        amount: i128,
+        anonymous_salt: Option<BytesN<32>>,
+        note: Option<DonationNote>,
+    ) {
+        if amount <= 0 {
+            panic_with_error!(env, Error::InvalidDonationAmount);
+        }
+
+        let request = donations::load_donatable(env, request_id);
+
+        let token_client = token::Client::new(env, &request.token);
+
+        token_client.transfer(donor, env.current_contract_address(), &amount);
+
+        donations::record(env, request, donor, amount, anonymous_salt, note);
+    }
+
+    /// Whether pledge settlement has started on the request and still has
+    /// pledges left to convert.
+    fn settling_pledges(env: &Env, request_id: u64) -> bool {
+        let cursor: u32 = match env
+            .storage()
+            .persistent()
+            .get(&PledgeKey::PledgeCursor(request_id))
+        {
+            Some(cursor) => cursor,
+            None => return false,
+        };
+        let pledger_count: u32 = env
+            .storage()
+            .persistent()
+            .get(&PledgeKey::PledgerCount(request_id))
+            .unwrap_or(0);
+        cursor < pledger_count
+    }
+
+    /// Take the configured posting bond from `creator`, unless their profile
+    /// is verified or no bond is configured.
+    fn lock_bond(env: &Env, creator: &Address, request_id: u64) {
+        let bond: PostingBond = match env.storage().instance().get(&DataKey::PostingBond) {
+            Some(bond) => bond,
+            None => return,
+        };
+
+        if env
+            .storage()
+            .persistent()
+            .has(&DataKey::VerifiedProfile(creator.clone()))
+        {
+            return;
+        }
+
+        let token_client = token::Client::new(env, &bond.token);
+        token_client.transfer(creator, env.current_contract_address(), &bond.amount);
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::RequestBond(request_id), &bond);
+
+        BondLocked {
+            request_id,
+            creator: creator.clone(),
+            token: bond.token,
+            amount: bond.amount,
+        }
+        .publish(env);
+    }
+
+    /// Give the posting bond back to the creator of a request that closed or
+    /// was cancelled cleanly.
+    fn return_bond(env: &Env, request: &HelpRequest) {
+        let bond_key = DataKey::RequestBond(request.id);
+        let bond: PostingBond = match env.storage().persistent().get(&bond_key) {
+            Some(bond) => bond,
+            None => return,
+        };
+        env.storage().persistent().remove(&bond_key);
+
+        let token_client = token::Client::new(env, &bond.token);
+        token_client.transfer(
+            &env.current_contract_address(),
+            &request.creator,
+            &bond.amount,
+        );
+
+        BondReturned {
+            request_id: request.id,
+            creator: request.creator.clone(),
+            token: bond.token,
+            amount: bond.amount,
+        }
+        .publish(env);
+    }
+
+    fn load_in_kind(env: &Env, pledge_id: u64) -> (InKindPledge, HelpRequest) {
+        let pledge: InKindPledge = env
+            .storage()
+            .persistent()
+            .get(&InKindKey::InKindPledge(pledge_id))
+            .unwrap_or_else(|| panic_with_error!(env, Error::InKindPledgeNotFound));
+        let request: HelpRequest = env
+            .storage()
+            .persistent()
+            .get(&DataKey::HelpRequest(pledge.request_id))
+            .unwrap_or_else(|| panic_with_error!(env, Error::HelpRequestNotFound));
+        (pledge, request)
+    }
+
+    /// Mark an in-kind pledge delivered, return the helper's bond and count
+    /// the delivery on the profiles of the helper and of whoever received
+    /// the help. Users without a profile are not counted.
+    fn complete_delivery(env: &Env, pledge: &mut InKindPledge, request: &HelpRequest) {
+        pledge.status = InKindStatus::Delivered;
+        env.storage()
+            .persistent()
+            .set(&InKindKey::InKindPledge(pledge.id), pledge);
+
+        if pledge.bond > 0 {
+            let token_client = token::Client::new(env, &request.token);
+            token_client.transfer(
+                &env.current_contract_address(),
+                &pledge.helper,
+                &pledge.bond,
+            );
+        }
+
+        let recipient = request
+            .beneficiary
+            .clone()
+            .unwrap_or(request.creator.clone());
+        let helper_key = DataKey::Profile(pledge.helper.clone());
+        if let Some(mut profile) = env
+            .storage()
+            .persistent()
+            .get::<_, ProfileData>(&helper_key)
+        {
+            profile.in_kind_given = profile.in_kind_given.saturating_add(1);
+            env.storage().persistent().set(&helper_key, &profile);
+        }
+        let recipient_key = DataKey::Profile(recipient);
+        if let Some(mut profile) = env
+            .storage()
+            .persistent()
+            .get::<_, ProfileData>(&recipient_key)
+        {
+            profile.in_kind_received = profile.in_kind_received.saturating_add(1);
+            env.storage().persistent().set(&recipient_key, &profile);
+        }
+
+        DeliveryConfirmed {
+            pledge_id: pledge.id,
+            request_id: pledge.request_id,
+            helper: pledge.helper.clone(),
+        }
+        .publish(env);
+    }
+
+    /// Refund `donor`'s primary-token donation to a cancelled request.
+    /// Returns `false` if the donor has nothing left to refund.
+    fn refund_donor(env: &Env, request: &HelpRequest, donor: &Address) -> bool {
+        let donation_key = DataKey::Donation(request.id, donor.clone());
+        let donation: i128 = env.storage().persistent().get(&donation_key).unwrap_or(0);
+
+        if donation <= 0 {
+            return false;
+        }
+
+        let amount = Self::refund_share(
+            env,
+            donation,
+            request.raised_amount,
+            request.disbursed_amount,
+        );
+
+        if amount > 0 {
+            let token_client = token::Client::new(env, &request.token);
+            token_client.transfer(&env.current_contract_address(), donor, &amount);
+        }
+
+        // Reset donation amount to prevent double refund
+        env.storage().persistent().set(&donation_key, &0i128);
+
+        // Take the refunded fraction of what the donor gave themselves off
+        // their totals; pool matches and round allocations were never on it
+        let given: i128 = env
+            .storage()
+            .persistent()
+            .get(&LeaderboardKey::Given(request.id, donor.clone()))
+            .unwrap_or(0);
+        let refunded_given = given
+            .checked_mul(amount)
+            .map(|v| v / donation)
+            .unwrap_or_else(|| panic_with_error!(env, Error::ArithmeticOverflow));
+        donations::record_giving(env, request, donor, -refunded_given);
+
+        match donations::donor_commitment(env, request.id, donor) {
+            Some(commitment) => AnonymousRefundClaimed {
+                request_id: request.id,
+                commitment,
+                amount,
+            }
+            .publish(env),
+            None => RefundClaimed {
+                request_id: request.id,
+                donor: donor.clone(),
+                amount,
+            }
+            .publish(env),
+        }
+
+        true
+    }
+
+    /// Refund `donor`'s balance in a secondary `token` of a cancelled request.
+    /// Returns `false` if the donor has nothing left to refund in that token.
+    fn refund_token_donor(env: &Env, request_id: u64, token: &Address, donor: &Address) -> bool {
+        let donation_key = DataKey::TokenDonation(request_id, token.clone(), donor.clone());
+        let donation: i128 = env.storage().persistent().get(&donation_key).unwrap_or(0);
+
+        if donation <= 0 {
+            return false;
+        }
+
+        let raised: i128 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::TokenRaised(request_id, token.clone()))
+            .unwrap_or(0);
+        let disbursed: i128 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::TokenDisbursed(request_id, token.clone()))
+            .unwrap_or(0);
+
+        let amount = Self::refund_share(env, donation, raised, disbursed);
+
+        if amount > 0 {
+            let token_client = token::Client::new(env, token);
+            token_client.transfer(&env.current_contract_address(), donor, &amount);
+        }
+
+        env.storage().persistent().set(&donation_key, &0i128);
+        donations::record_token_giving(env, token, donor, -amount);
+
+        match donations::donor_commitment(env, request_id, donor) {
+            Some(commitment) => AnonymousTokenRefundClaimed {
+                request_id,
+                commitment,
+                token: token.clone(),
+                amount,
+            }
+            .publish(env),
+            None => TokenRefundClaimed {
+                request_id,
+                donor: donor.clone(),
+                token: token.clone(),
+                amount,
+            }
+            .publish(env),
+        }
+
+        true
+    }
+
+    /// Undo one recent donation and everything it triggered, except the
+    /// refund transfer to the donor.
+    ///
+    /// # Panics
+    /// Panics with `Error::InvalidRound` if the donation was counted in a
+    /// funding round that has since been finalized, since its matching funds
+    /// have already been paid out.
+    fn unwind_donation(
+        env: &Env,
+        request: &mut HelpRequest,
+        donor: &Address,
+        recent: &RecentDonation,
+    ) {
+        let request_id = request.id;
+
+        if let Some(round_id) = recent.round_id {
+            let round: Option<FundingRound> = env
+                .storage()
+                .persistent()
+                .get(&RoundKey::FundingRound(round_id));
+            match round.map(|round| round.status) {
+                Some(RoundStatus::Active) => donations::adjust_round_contribution(
+                    env,
+                    round_id,
+                    request_id,
+                    donor,
+                    -recent.amount,
+                ),
+                Some(RoundStatus::Finalized) => panic_with_error!(env, Error::InvalidRound),
+                _ => {}
+            }
+        }
+
+        Self::reduce_donation(env, request_id, donor, recent.amount);
+        donations::record_giving(env, request, donor, -recent.amount);
+        request.raised_amount -= recent.amount;
+
+        for (pool_id, matched) in recent.matches.iter() {
+            let pool_key = MatchingKey::MatchingPool(pool_id);
+            let mut pool: MatchingPool = match env.storage().persistent().get(&pool_key) {
+                Some(pool) => pool,
+                None => continue,
+            };
+
+            let matched_key = MatchingKey::PoolMatched(pool_id, request_id);
+            let pool_matched: i128 = env.storage().persistent().get(&matched_key).unwrap_or(0);
+            env.storage()
+                .persistent()
+                .set(&matched_key, &(pool_matched - matched));
+
+            Self::reduce_donation(env, request_id, &pool.sponsor, matched);
+            request.raised_amount -= matched;
+
+            if pool.is_active {
+                pool.balance += matched;
+                env.storage().persistent().set(&pool_key, &pool);
+            } else {
+                let token_client = token::Client::new(env, &pool.token);
+                token_client.transfer(&env.current_contract_address(), &pool.sponsor, &matched);
+            }
+        }
+
+        if recent.value > 0 {
+            let valued_key = DataKey::ValuedRaised(request_id);
+            let valued_raised: i128 = env.storage().persistent().get(&valued_key).unwrap_or(0);
+            env.storage()
+                .persistent()
+                .set(&valued_key, &(valued_raised - recent.value));
+        }
+    }
+
+    /// Whether anything has been withdrawn from `request`, in its primary
+    /// token or any secondary token.
+    fn has_disbursed(env: &Env, request: &HelpRequest) -> bool {
+        if request.disbursed_amount != 0 {
+            return true;
+        }
+
+        let tokens: Vec<Address> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::RequestTokens(request.id))
+            .unwrap_or(Vec::new(env));
+        tokens.iter().any(|token| {
+            env.storage()
+                .persistent()
+                .get::<_, i128>(&DataKey::TokenDisbursed(request.id, token))
+                .unwrap_or(0)
+                != 0
+        })
+    }
+
+    fn reduce_donation(env: &Env, request_id: u64, donor: &Address, amount: i128) {
+        let donation_key = DataKey::Donation(request_id, donor.clone());
+        let donation: i128 = env.storage().persistent().get(&donation_key).unwrap_or(0);
+        let remaining = donation
+            .checked_sub(amount)
+            .filter(|remaining| *remaining >= 0)
+            .unwrap_or_else(|| panic_with_error!(env, Error::ArithmeticOverflow));
+        env.storage().persistent().set(&donation_key, &remaining);
+    }
+
+    fn store_revision(env: &Env, request: &HelpRequest) {
+        let revision = HelpRequestRevision {
+            revision: request.revision,
+            goal: request.goal,
+            content_hash: request.metadata.content_hash.clone(),
+            updated_at: env.ledger().timestamp(),
+        };
+        env.storage().persistent().set(
+            &DataKey::HelpRequestRevision(request.id, request.revision),
+            &revision,
+        );
+    }
+
+    /// Address withdrawals of `request` are paid to.
+    ///
+    /// # Panics
+    /// Panics with `Error::BeneficiaryNotConfirmed` if the request has a
+    /// beneficiary that has not confirmed yet.
+    fn payout_recipient(env: &Env, request: &HelpRequest) -> Address {
+        match &request.beneficiary {
+            Some(beneficiary) => {
+                if !request.beneficiary_confirmed {
+                    panic_with_error!(env, Error::BeneficiaryNotConfirmed);
+                }
+                beneficiary.clone()
+            }
+            None => request.creator.clone(),
+        }
+    }
+
+    /// Secondary-token donations to `request`, valued in units of its primary
+    /// token: `donor`'s outstanding donations, or everything raised when
+    /// `donor` is `None`. Returns 0 until a price oracle is configured.
+    /// Tokens without a fresh price are left out, and so is everything when
+    /// the primary token has none.
+    fn secondary_in_primary_units(
+        env: &Env,
+        request: &HelpRequest,
+        donor: Option<&Address>,
+    ) -> i128 {
+        if !env.storage().instance().has(&DataKey::PriceOracle) {
+            return 0;
+        }
+
+        let tokens: Vec<Address> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::RequestTokens(request.id))
+            .unwrap_or(Vec::new(env));
+        if tokens.is_empty() {
+            return 0;
+        }
+
+        // Value of one whole primary token
+        let one_primary = 10i128
+            .checked_pow(token::Client::new(env, &request.token).decimals())
+            .unwrap_or_else(|| panic_with_error!(env, Error::ArithmeticOverflow));
+        let primary_value = match donations::try_value_of(env, &request.token, one_primary) {
+            Ok(value) if value > 0 => value,
+            _ => return 0,
+        };
+
+        let mut total: i128 = 0;
+        for token in tokens.iter() {
+            let amount: i128 = match donor {
+                Some(donor) => env.storage().persistent().get(&DataKey::TokenDonation(
+                    request.id,
+                    token.clone(),
+                    donor.clone(),
+                )),
+                None => env
+                    .storage()
+                    .persistent()
+                    .get(&DataKey::TokenRaised(request.id, token.clone())),
+            }
+            .unwrap_or(0);
+            if amount <= 0 {
+                continue;
+            }
+
+            // A token without a fresh price must not block reports
+            let value = match donations::try_value_of(env, &token, amount) {
+                Ok(value) => value,
+                Err(_) => continue,
+            };
+            total = value
+                .checked_mul(one_primary)
+                .map(|v| v / primary_value)
+                .and_then(|v| total.checked_add(v))
+                .unwrap_or_else(|| panic_with_error!(env, Error::ArithmeticOverflow));
+        }
+
+        total
+    }
+
+    /// Share of `donation` still held by the contract out of a balance that
+    /// has received `raised` and paid out `disbursed`.
+    ///
+    /// Every donor is refunded the same fraction of what they gave:
+    /// `donation * (raised - disbursed) / raised`, rounded down, so the sum of
+    /// all refunds can never exceed the undisbursed balance.
+    fn refund_share(env: &Env, donation: i128, raised: i128, disbursed: i128) -> i128 {
+        if disbursed == 0 {
+            return donation;
+        }
+
+        let remaining = raised
+            .checked_sub(disbursed)
+            .unwrap_or_else(|| panic_with_error!(env, Error::ArithmeticOverflow));
+
+        donation
+            .checked_mul(remaining)
+            .and_then(|v| v.checked_div(raised))
+            .unwrap_or_else(|| panic_with_error!(env, Error::ArithmeticOverflow))
+    }
 }
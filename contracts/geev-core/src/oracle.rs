@@ -0,0 +1,31 @@
+use soroban_sdk::{contractclient, contracttype, Address, Env, Symbol};
+
+/// Asset identifier used by SEP-40 price feeds.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum Asset {
+    /// A Stellar asset, identified by its token contract address.
+    Stellar(Address),
+    /// Any other asset, such as a fiat currency, identified by its ticker.
+    Other(Symbol),
+}
+
+/// Latest price reported by an oracle for an asset, scaled by
+/// `10^decimals()` of the oracle.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct PriceData {
+    pub price: i128,
+    pub timestamp: u64,
+}
+
+/// Subset of the SEP-40 price-feed interface used to value donations
+/// against goals denominated in a reference currency.
+#[contractclient(name = "PriceOracleClient")]
+pub trait PriceOracle {
+    /// Most recent price of `asset` in the reference currency, if known.
+    fn lastprice(env: Env, asset: Asset) -> Option<PriceData>;
+
+    /// Number of decimals used by `PriceData::price`.
+    fn decimals(env: Env) -> u32;
+}
@@ -29,21 +29,24 @@ impl ProfileContract {
         }
 
         // Free the old username mapping when a user changes their handle
-        if let Some(existing) = env
+        let existing = env
             .storage()
             .persistent()
-            .get::<DataKey, ProfileData>(&profile_key)
-        {
+            .get::<DataKey, ProfileData>(&profile_key);
+        if let Some(existing) = &existing {
             if existing.username != username {
                 env.storage()
                     .persistent()
-                    .remove(&DataKey::Username(existing.username));
+                    .remove(&DataKey::Username(existing.username.clone()));
             }
         }
 
+        // Delivery counts are kept across profile edits
         let profile = ProfileData {
             username: username.clone(),
             avatar_hash,
+            in_kind_given: existing.as_ref().map_or(0, |p| p.in_kind_given),
+            in_kind_received: existing.as_ref().map_or(0, |p| p.in_kind_received),
         };
 
         env.storage().persistent().set(&profile_key, &profile);
@@ -52,6 +55,13 @@ impl ProfileContract {
         env.storage().persistent().set(&username_key, &user);
     }
 
+    /// Whether an admin has verified this user's profile.
+    pub fn is_verified(env: Env, user: Address) -> bool {
+        env.storage()
+            .persistent()
+            .has(&DataKey::VerifiedProfile(user))
+    }
+
     /// Retrieve profile data for a given wallet address.
     /// Returns `None` if no profile has been registered for that address.
     pub fn get_profile(env: Env, user: Address) -> Option<ProfileData> {
@@ -0,0 +1,175 @@
+use crate::types::{DataKey, Error, HelpRequest, HelpRequestStatus, LimitKey, PostingBond};
+use soroban_sdk::{contractevent, panic_with_error, Address, Env};
+
+#[contractevent]
+pub struct RequestCancelled {
+    request_id: u64,
+    creator: Address,
+    beneficiary: Option<Address>,
+}
+
+#[contractevent]
+pub struct RequestFrozen {
+    request_id: u64,
+    reason: u32,
+}
+
+#[contractevent]
+pub struct RequestUnfrozen {
+    request_id: u64,
+}
+
+#[contractevent]
+pub struct RequestForceCancelled {
+    request_id: u64,
+    reason: u32,
+}
+
+#[contractevent]
+pub struct BondForfeited {
+    request_id: u64,
+    token: Address,
+    amount: i128,
+}
+
+/// Whether `request` has reached its goal, in the reference currency when
+/// a valued goal is set and in its primary token otherwise.
+pub fn goal_reached(env: &Env, request: &HelpRequest) -> bool {
+    goal_reached_with(env, request, 0, 0)
+}
+
+/// Whether `request` would reach its goal with `extra` more of its primary
+/// token, worth `extra_value` in the reference currency.
+pub fn goal_reached_with(env: &Env, request: &HelpRequest, extra: i128, extra_value: i128) -> bool {
+    match env
+        .storage()
+        .persistent()
+        .get::<_, i128>(&DataKey::ValuedGoal(request.id))
+    {
+        Some(valued_goal) => {
+            let valued_raised: i128 = env
+                .storage()
+                .persistent()
+                .get(&DataKey::ValuedRaised(request.id))
+                .unwrap_or(0);
+            valued_raised.saturating_add(extra_value) >= valued_goal
+        }
+        None => request.raised_amount.saturating_add(extra) >= request.goal,
+    }
+}
+
+/// Move a request to `status`, keeping its creator's active request
+/// count in step. Open, FullyFunded and Frozen requests are active;
+/// Closed and Cancelled ones no longer count towards the limit.
+pub fn set_status(env: &Env, request: &mut HelpRequest, status: HelpRequestStatus) {
+    let was_active = is_active(request.status);
+    request.status = status;
+
+    if was_active == is_active(status) {
+        return;
+    }
+
+    let count_key = LimitKey::ActiveRequestCount(request.creator.clone());
+    let active: u32 = env.storage().persistent().get(&count_key).unwrap_or(0);
+    let active = if was_active {
+        active.saturating_sub(1)
+    } else {
+        active.saturating_add(1)
+    };
+    env.storage().persistent().set(&count_key, &active);
+}
+
+pub fn is_active(status: HelpRequestStatus) -> bool {
+    matches!(
+        status,
+        HelpRequestStatus::Open | HelpRequestStatus::FullyFunded | HelpRequestStatus::Frozen
+    )
+}
+
+/// Cancel `request` and store it. Donors can then claim refunds.
+pub fn cancel(env: &Env, request: &mut HelpRequest) {
+    set_status(env, request, HelpRequestStatus::Cancelled);
+    env.storage()
+        .persistent()
+        .set(&DataKey::HelpRequest(request.id), request);
+
+    RequestCancelled {
+        request_id: request.id,
+        creator: request.creator.clone(),
+        beneficiary: request.beneficiary.clone(),
+    }
+    .publish(env);
+}
+
+pub fn freeze(env: &Env, request: &mut HelpRequest, reason: u32) {
+    set_status(env, request, HelpRequestStatus::Frozen);
+    request.moderation_reason = Some(reason);
+    env.storage()
+        .persistent()
+        .set(&DataKey::HelpRequest(request.id), request);
+
+    RequestFrozen {
+        request_id: request.id,
+        reason,
+    }
+    .publish(env);
+}
+
+/// Return a frozen request to FullyFunded if its goal has been reached,
+/// otherwise to Open.
+pub fn unfreeze(env: &Env, request: &mut HelpRequest) {
+    let status = if goal_reached(env, request) {
+        HelpRequestStatus::FullyFunded
+    } else {
+        HelpRequestStatus::Open
+    };
+    set_status(env, request, status);
+    request.moderation_reason = None;
+    env.storage()
+        .persistent()
+        .set(&DataKey::HelpRequest(request.id), request);
+
+    RequestUnfrozen {
+        request_id: request.id,
+    }
+    .publish(env);
+}
+
+pub fn force_cancel(env: &Env, request: &mut HelpRequest, reason: u32) {
+    request.moderation_reason = Some(reason);
+    forfeit_bond(env, request.id);
+
+    RequestForceCancelled {
+        request_id: request.id,
+        reason,
+    }
+    .publish(env);
+    cancel(env, request);
+}
+
+/// Move the posting bond of a force-cancelled request into the fee pool.
+fn forfeit_bond(env: &Env, request_id: u64) {
+    let bond_key = DataKey::RequestBond(request_id);
+    let bond: PostingBond = match env.storage().persistent().get(&bond_key) {
+        Some(bond) => bond,
+        None => return,
+    };
+    env.storage().persistent().remove(&bond_key);
+
+    let fees_key = DataKey::CollectedFees(bond.token.clone());
+    let fees: i128 = env
+        .storage()
+        .persistent()
+        .get::<_, i128>(&fees_key)
+        .unwrap_or(0)
+        .checked_add(bond.amount)
+        .unwrap_or_else(|| panic_with_error!(env, Error::ArithmeticOverflow));
+    env.storage().persistent().set(&fees_key, &fees);
+
+    BondForfeited {
+        request_id,
+        token: bond.token,
+        amount: bond.amount,
+    }
+    .publish(env);
+}
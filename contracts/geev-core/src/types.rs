@@ -1,4 +1,4 @@
-use soroban_sdk::{contracterror, contracttype, Address, String};
+use soroban_sdk::{contracterror, contracttype, Address, BytesN, Map, String};
 
 #[contracterror]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
@@ -22,6 +22,38 @@ pub enum Error {
     HelpRequestAlreadyExists = 16,
     TokenNotSupported = 17,
     UsernameTaken = 18,
+    InvalidWithdrawalAmount = 19,
+    MatchingPoolNotFound = 20,
+    InvalidMatchingPool = 21,
+    NotSponsor = 22,
+    RoundNotFound = 23,
+    InvalidRound = 24,
+    RoundStillActive = 25,
+    PriceUnavailable = 26,
+    StalePrice = 27,
+    BeneficiaryNotConfirmed = 28,
+    NotBeneficiary = 29,
+    GracePeriodExpired = 30,
+    AlreadyReported = 31,
+    InvalidThreshold = 32,
+    InvalidBondAmount = 33,
+    CreatorLimitReached = 34,
+    PledgingClosed = 35,
+    PledgeConditionNotMet = 36,
+    SubscriptionNotFound = 37,
+    InvalidSubscription = 38,
+    SubscriptionNotDue = 39,
+    InKindPledgeNotFound = 40,
+    InvalidHelpType = 41,
+    EscrowNotFound = 42,
+    InvalidEscrow = 43,
+    NotEscrowParty = 44,
+    DeadlinePassed = 45,
+    DeadlineNotReached = 46,
+    RequestNotExpired = 47,
+    InvalidDonationNote = 48,
+    NotWinner = 49,
+    InvalidForwardShare = 50,
 }
 
 #[derive(Clone, PartialEq, Eq)]
@@ -30,6 +62,7 @@ pub enum GiveawayStatus {
     Active = 0,
     Claimable = 1,
     Completed = 2,
+    Cancelled = 3,
 }
 
 #[derive(Clone)]
@@ -53,6 +86,36 @@ pub enum HelpRequestStatus {
     FullyFunded = 1,
     Closed = 2,
     Cancelled = 3,
+    Frozen = 4,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[contracttype]
+pub enum HelpType {
+    Material = 0,
+    Service = 1,
+    Advice = 2,
+    Other = 3,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[contracttype]
+pub enum Urgency {
+    Low = 0,
+    Medium = 1,
+    High = 2,
+    Urgent = 3,
+}
+
+/// Descriptive fields mirrored from the off-chain `Post`. `content_hash` is
+/// the IPFS CID of the title, description and media, so tampering with the
+/// off-chain copy can be detected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct HelpRequestMetadata {
+    pub help_type: HelpType,
+    pub urgency: Urgency,
+    pub content_hash: String,
 }
 
 #[derive(Clone)]
@@ -60,13 +123,117 @@ pub enum HelpRequestStatus {
 pub struct HelpRequest {
     pub id: u64,
     pub creator: Address,
+    /// Recipient of withdrawals when posted on someone else's behalf.
+    pub beneficiary: Option<Address>,
+    pub beneficiary_confirmed: bool,
     pub token: Address,
     pub goal: i128,
     pub raised_amount: i128,
+    pub disbursed_amount: i128,
     pub status: HelpRequestStatus,
     pub is_verified: bool,
+    pub metadata: HelpRequestMetadata,
+    pub revision: u32,
+    /// Reason code recorded by the admin when freezing or force-cancelling.
+    pub moderation_reason: Option<u32>,
+}
+
+/// Snapshot of the editable fields of a help request after each edit.
+/// Revision 0 is the request as originally posted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct HelpRequestRevision {
+    pub revision: u32,
+    pub goal: i128,
+    pub content_hash: String,
+    pub updated_at: u64,
+}
+
+/// Which help requests a matching pool is willing to match.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub enum MatchEligibility {
+    AllRequests,
+    VerifiedOnly,
+    Request(u64),
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct MatchingPool {
+    pub id: u64,
+    pub sponsor: Address,
+    pub token: Address,
+    /// Matched amount per donated unit, in basis points (10_000 = 1:1).
+    pub ratio_bps: u32,
+    /// Maximum amount the pool will match for any single request.
+    pub cap: i128,
+    pub balance: i128,
+    pub eligibility: MatchEligibility,
+    pub is_active: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[contracttype]
+pub enum RoundStatus {
+    Active = 0,
+    Finalized = 1,
+    Cancelled = 2,
+}
+
+/// A quadratic-funding round distributing `matching_amount` across the help
+/// requests that joined it.
+#[derive(Clone)]
+#[contracttype]
+pub struct FundingRound {
+    pub id: u64,
+    pub funder: Address,
+    pub token: Address,
+    pub matching_amount: i128,
+    pub start_time: u64,
+    pub end_time: u64,
+    /// Only donors with a registered `ProfileContract` profile count.
+    pub require_profile: bool,
+    pub status: RoundStatus,
 }
 
+/// A donor's recent donation to a request, kept so that it can be retracted
+/// during the grace period along with everything it triggered.
+#[derive(Clone)]
+#[contracttype]
+pub struct RecentDonation {
+    pub amount: i128,
+    pub donated_at: u64,
+    /// Reference-currency value added for the donation and its matches.
+    pub value: i128,
+    /// Funding round the donation was counted in, if any.
+    pub round_id: Option<u64>,
+    /// Amount matched by each matching pool, keyed by pool id.
+    pub matches: Map<u64, i128>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[contracttype]
+pub enum UpdateKind {
+    Progress = 0,
+    Receipt = 1,
+    ThankYou = 2,
+}
+
+/// Entry in a help request's creator update log.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[contracttype]
+pub struct RequestUpdate {
+    pub index: u32,
+    pub kind: UpdateKind,
+    pub content_hash: String,
+    pub posted_at: u64,
+}
+
+/// General storage keys. `contracttype` enums are limited to 50 variants,
+/// so the keys of larger features live in their own enums below. Variant
+/// names must stay unique across all key enums, since a key is stored as
+/// its variant name and fields.
 #[derive(Clone)]
 #[contracttype]
 pub enum DataKey {
@@ -83,6 +250,236 @@ pub enum DataKey {
     AllowedToken(Address),
     Profile(Address),
     Username(String),
+    TokenDonation(u64, Address, Address),
+    TokenRaised(u64, Address),
+    TokenDisbursed(u64, Address),
+    RequestTokens(u64),
+    PriceOracle,
+    MaxPriceAge,
+    ValuedGoal(u64),
+    ValuedRaised(u64),
+    HelpRequestRevision(u64, u32),
+    RequestUpdateCount(u64),
+    RequestUpdate(u64, u32),
+    HasSpendingReport(u64),
+    DonationGracePeriod,
+    RecentDonation(u64, Address),
+    HasDonated(u64, Address),
+    DonorCount(u64),
+    DonorIndex(u64, u32),
+    RefundCursor(u64),
+    Report(u64, Address),
+    ReportWeight(u64),
+    ReportThreshold,
+    PostingBond,
+    RequestBond(u64),
+    VerifiedProfile(Address),
+    MaxOpenDuration,
+    AnonymousDonor(u64, Address),
+    ContributionCount(u64),
+    Contribution(u64, u32),
+}
+
+/// Storage keys for sponsor matching pools.
+#[derive(Clone)]
+#[contracttype]
+pub enum MatchingKey {
+    MatchingPoolCounter,
+    MatchingPool(u64),
+    TokenPools(Address),
+    RequestPools(u64),
+    PoolMatched(u64, u64),
+    MinPoolDeposit(Address),
+}
+
+/// Storage keys for quadratic-funding rounds.
+#[derive(Clone)]
+#[contracttype]
+pub enum RoundKey {
+    RoundCounter,
+    FundingRound(u64),
+    RoundRequests(u64),
+    RequestRound(u64),
+    RoundContribution(u64, u64, Address),
+    RoundSqrtSum(u64, u64),
+}
+
+/// Storage keys for the per-creator active request and giveaway limits.
+#[derive(Clone)]
+#[contracttype]
+pub enum LimitKey {
+    MaxActiveRequests,
+    MaxActiveGiveaways,
+    ActiveRequestCount(Address),
+    ActiveGiveawayCount(Address),
+}
+
+/// Storage keys for conditional pledges.
+#[derive(Clone)]
+#[contracttype]
+pub enum PledgeKey {
+    PledgeDeadline(u64),
+    Pledge(u64, Address),
+    PledgedTotal(u64),
+    PledgerCount(u64),
+    PledgerIndex(u64, u32),
+    PledgeCursor(u64),
+}
+
+/// Storage keys for recurring donations.
+#[derive(Clone)]
+#[contracttype]
+pub enum SubscriptionKey {
+    SubscriptionCounter,
+    Subscription(u64),
+}
+
+/// Storage keys for in-kind pledges.
+#[derive(Clone)]
+#[contracttype]
+pub enum InKindKey {
+    InKindCounter,
+    InKindPledge(u64),
+}
+
+/// Storage keys for escrows.
+#[derive(Clone)]
+#[contracttype]
+pub enum EscrowKey {
+    EscrowCounter,
+    Escrow(u64),
+}
+
+/// Storage keys for donor leaderboards.
+#[derive(Clone)]
+#[contracttype]
+pub enum LeaderboardKey {
+    TotalGiven(Address, Address),
+    /// Part of a donor's donation to a request that counts towards their
+    /// public totals: what they gave themselves, without pool matches or
+    /// round allocations, and only while giving publicly.
+    Given(u64, Address),
+    TopDonors(u64),
+    TopDonorsByToken(Address),
+}
+
+/// Message and dedication a donor can attach to a donation. `message_hash`
+/// is the sha256 of the message text kept off-chain, so the app can show
+/// the message and anyone can check it against the chain.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct DonationNote {
+    pub message_hash: Option<BytesN<32>>,
+    pub dedicated_to: Option<String>,
+}
+
+/// One donation to a help request, kept in the request's contribution log
+/// alongside the cumulative `DataKey::Donation` total. `donor` is `None`
+/// for anonymous donations.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Contribution {
+    pub index: u32,
+    pub donor: Option<Address>,
+    pub amount: i128,
+    pub message_hash: Option<BytesN<32>>,
+    pub dedicated_to: Option<String>,
+    pub donated_at: u64,
+}
+
+/// Recurring donation pulled from the donor's token allowance once per
+/// `period` until `end_time`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Subscription {
+    pub id: u64,
+    pub donor: Address,
+    pub request_id: u64,
+    pub amount: i128,
+    pub period: u64,
+    pub end_time: u64,
+    pub next_due: u64,
+    pub is_paused: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[contracttype]
+pub enum InKindStatus {
+    Pending = 0,
+    Delivered = 1,
+    Disputed = 2,
+    Failed = 3,
+    Expired = 4,
+}
+
+/// Offer of goods or a service towards a Material or Service request,
+/// described off-chain by `description_hash`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct InKindPledge {
+    pub id: u64,
+    pub request_id: u64,
+    pub helper: Address,
+    pub description_hash: String,
+    pub bond: i128,
+    /// After this time the helper can take back the bond of an unconfirmed,
+    /// undisputed pledge.
+    pub delivery_deadline: u64,
+    pub status: InKindStatus,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[contracttype]
+pub enum EscrowStatus {
+    Funded = 0,
+    Delivered = 1,
+    Released = 2,
+    Refunded = 3,
+    Disputed = 4,
+    Resolved = 5,
+}
+
+/// What the funder agrees to when locking money for a provider. Each
+/// timeout, in seconds, bounds one step of the deal: delivery after
+/// funding, the funder's review after delivery, and the arbiter's decision
+/// after a dispute.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct EscrowTerms {
+    pub provider: Address,
+    pub arbiter: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub request_id: Option<u64>,
+    pub delivery_timeout: u64,
+    pub review_timeout: u64,
+    pub dispute_timeout: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Escrow {
+    pub id: u64,
+    pub funder: Address,
+    pub terms: EscrowTerms,
+    pub status: EscrowStatus,
+    /// End of the current step; meaning depends on `status`.
+    pub deadline: u64,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct LeaderboardEntry {
+    pub donor: Address,
+    pub amount: i128,
+}
+
+/// Deposit locked by a creator when posting a help request.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct PostingBond {
+    pub token: Address,
+    pub amount: i128,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -90,4 +487,9 @@ pub enum DataKey {
 pub struct ProfileData {
     pub username: String,
     pub avatar_hash: String,
+    /// In-kind pledges the user has delivered, as confirmed by the requester
+    /// or by admin arbitration.
+    pub in_kind_given: u32,
+    /// In-kind pledges delivered to the user.
+    pub in_kind_received: u32,
 }
@@ -14,3 +14,17 @@ where
     env.storage().temporary().remove(&LOCK_KEY);
     result
 }
+
+/// Integer square root, rounded down. Non-positive inputs return 0.
+pub fn isqrt(n: i128) -> i128 {
+    if n <= 0 {
+        return 0;
+    }
+    let mut x = n;
+    let mut y = n / 2 + (n & 1);
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+    x
+}